@@ -19,10 +19,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 		println!("cargo:warning=Using vendored protoc from: {:?}", protoc_path);
 	}
 
+	let out_dir = std::env::var("OUT_DIR")?;
+
 	// compile your proto(s)
-	for p in ["./protos/encryption.proto", "./protos/election.proto", "./protos/directoryofservice.proto"].iter() {
-		tonic_prost_build::compile_protos(p)?;
-	}
+	// Default stubs only for election.proto, whose `Bully` trait grows new RPCs
+	// that existing implementations must not have to add. The encryption and
+	// directory traits stay fully required, so a missing RPC is a compile error.
+	let election_descriptor = Path::new(&out_dir).join("election_descriptor.bin");
+	let others_descriptor = Path::new(&out_dir).join("others_descriptor.bin");
+	tonic_prost_build::configure()
+		.file_descriptor_set_path(&election_descriptor)
+		.generate_default_stubs(true)
+		.compile_protos(&["./protos/election.proto"], &["./protos"])?;
+	tonic_prost_build::configure()
+		.file_descriptor_set_path(&others_descriptor)
+		.compile_protos(&["./protos/encryption.proto", "./protos/directoryofservice.proto"], &["./protos"])?;
+
+	// A FileDescriptorSet is a single repeated field, so concatenating two encoded
+	// sets yields one that covers every service; see `services::FILE_DESCRIPTOR_SET`.
+	let mut descriptor_set = fs::read(&election_descriptor)?;
+	descriptor_set.extend(fs::read(&others_descriptor)?);
+	fs::write(Path::new(&out_dir).join("services_descriptor.bin"), descriptor_set)?;
 
 	// Ensure Cargo rebuilds when proto changes.
 	println!("cargo:rerun-if-changed=./protos/encryption.proto");
@@ -32,11 +49,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 	// Copy the generated files from OUT_DIR to a stable location inside src/.
 	// This prevents IDEs / rust-analyzer from choking when OUT_DIR is not set.
-	let out_dir = std::env::var("OUT_DIR")?;
 	let dest_dir = Path::new("src").join("generated");
 	fs::create_dir_all(&dest_dir)?;
 
-	for fname in &["encryption.rs", "election.rs", "directoryofservice.rs"] {
+	for fname in &["encryption.rs", "election.rs", "directoryofservice.rs", "services_descriptor.bin"] {
 		let generated_src = Path::new(&out_dir).join(fname);
 		let dest = dest_dir.join(fname);
 		// If the generated file exists in OUT_DIR, copy it; else ignore (build will fail later if necessary).
@@ -0,0 +1,142 @@
+use std::collections::{HashSet, VecDeque};
+
+use tonic::Status;
+
+use super::Coordinator;
+
+/// Announcement ids a [`RecentAnnouncements`] remembers by default.
+pub const DEFAULT_RECENT_ANNOUNCEMENTS: usize = 1024;
+
+/// Bounded memory of recently seen `Coordinator.announcement_id`s, to drop exact duplicates
+/// (retries, re-broadcasts) before doing any work for them.
+///
+/// Keeps the last `capacity` distinct ids and forgets the oldest first. This only suppresses
+/// identical messages; stale announcements still need the epoch check.
+///
+/// ```
+/// use services::election_service::{Coordinator, Node, RecentAnnouncements};
+///
+/// let mut seen = RecentAnnouncements::default();
+/// let mut leader_changes = 0;
+/// let mut deliver = |announcement: &Coordinator| {
+///     if seen.insert(announcement) {
+///         leader_changes += 1;
+///     }
+/// };
+///
+/// let announcement = Coordinator {
+///     leader: Some(Node { id: 9, ..Default::default() }),
+///     epoch: 3,
+///     announcement_id: "5f0c7d4e-announce".to_string(),
+///     ..Default::default()
+/// };
+/// deliver(&announcement);
+/// deliver(&announcement);
+/// assert_eq!(leader_changes, 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecentAnnouncements {
+	capacity: usize,
+	order: VecDeque<String>,
+	seen: HashSet<String>,
+}
+
+impl Default for RecentAnnouncements {
+	fn default() -> Self {
+		Self::new(DEFAULT_RECENT_ANNOUNCEMENTS)
+	}
+}
+
+impl RecentAnnouncements {
+	/// Remembers up to `capacity` ids.
+	pub fn new(capacity: usize) -> Self {
+		Self { capacity, order: VecDeque::with_capacity(capacity), seen: HashSet::with_capacity(capacity) }
+	}
+
+	/// Records `announcement` and returns whether its id had not been seen yet.
+	///
+	/// Announcements without an id (from peers before protocol v2) can't be told apart and
+	/// always count as new.
+	pub fn insert(&mut self, announcement: &Coordinator) -> bool {
+		let id = &announcement.announcement_id;
+		if id.is_empty() || self.capacity == 0 {
+			return true;
+		}
+		if self.seen.contains(id) {
+			return false;
+		}
+		if self.order.len() == self.capacity
+			&& let Some(oldest) = self.order.pop_front()
+		{
+			self.seen.remove(&oldest);
+		}
+		self.order.push_back(id.clone());
+		self.seen.insert(id.clone());
+		true
+	}
+}
+
+impl Coordinator {
+	/// The copy of this announcement to re-broadcast, or `None` once its hop budget is spent.
+	///
+	/// Decrements `max_hops`; an announcement received with `max_hops == 0` is delivered but not
+	/// forwarded. Without a `max_hops` the copy is unchanged and relaying is left to epoch and
+	/// [`RecentAnnouncements`] dedup.
+	///
+	/// ```
+	/// use services::election_service::{Coordinator, Node};
+	///
+	/// let announcement = Coordinator {
+	///     leader: Some(Node { id: 9, ..Default::default() }),
+	///     epoch: 3,
+	///     max_hops: Some(2),
+	///     ..Default::default()
+	/// };
+	///
+	/// // Relay along a chain of nodes, each forwarding what it received to the next.
+	/// let mut forwards = 0;
+	/// let mut inbound = Some(announcement);
+	/// while let Some(received) = inbound {
+	///     inbound = received.forwarded();
+	///     forwards += inbound.is_some() as usize;
+	/// }
+	/// assert_eq!(forwards, 2);
+	///
+	/// let unbounded = Coordinator { max_hops: None, ..Default::default() };
+	/// assert_eq!(unbounded.forwarded(), Some(unbounded));
+	/// ```
+	pub fn forwarded(&self) -> Option<Coordinator> {
+		let max_hops = match self.max_hops {
+			Some(0) => return None,
+			Some(hops) => Some(hops - 1),
+			None => None,
+		};
+		Some(Coordinator { max_hops, ..self.clone() })
+	}
+}
+
+/// Rejects an announced `incoming` epoch that runs more than `max_delta` ahead of the `local` one.
+///
+/// Epochs only ever grow, so a peer that announces a near-`u64::MAX` epoch, buggy or malicious,
+/// would leave no room for any later legitimate announcement. Call this before adopting an
+/// epoch; the rejection is `Status::invalid_argument`. Epochs at or below `local` pass, since
+/// stale announcements are the regular epoch check's business.
+///
+/// ```
+/// use services::election_service::check_epoch_jump;
+///
+/// assert!(check_epoch_jump(41, 42, 1_000).is_ok());
+/// assert!(check_epoch_jump(41, 7, 1_000).is_ok());
+///
+/// let status = check_epoch_jump(41, u64::MAX, 1_000).unwrap_err();
+/// assert_eq!(status.code(), tonic::Code::InvalidArgument);
+/// assert_eq!(status.message(), format!("epoch {} is more than 1000 ahead of local epoch 41", u64::MAX));
+/// ```
+pub fn check_epoch_jump(local: u64, incoming: u64, max_delta: u64) -> Result<(), Status> {
+	if incoming.saturating_sub(local) > max_delta {
+		return Err(Status::invalid_argument(format!(
+			"epoch {incoming} is more than {max_delta} ahead of local epoch {local}"
+		)));
+	}
+	Ok(())
+}
@@ -0,0 +1,43 @@
+//! Names of optional election features, exchanged in `PingRequest.capabilities` and
+//! `PingResponse.capabilities` so mixed-version clusters only use what a peer supports.
+//!
+//! ```
+//! use services::election_service::bully_client::BullyClient;
+//! use services::election_service::{capabilities, PingRequest};
+//! use services::testing::RecordingBully;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let addr = RecordingBully::new().serve()?;
+//!
+//! let mut client = BullyClient::connect(format!("http://{addr}")).await?;
+//! let reply = client.ping(PingRequest::default()).await?.into_inner();
+//! assert!(capabilities::supports(&reply.capabilities, capabilities::SUBSCRIBE_COORDINATOR));
+//! // The mock is a single group, so it does not claim group routing.
+//! assert!(!capabilities::supports(&reply.capabilities, capabilities::ELECTION_GROUPS));
+//! # Ok(())
+//! # }
+//! ```
+
+/// `SubscribeCoordinator` streams leader changes.
+pub const SUBSCRIBE_COORDINATOR: &str = "subscribe_coordinator";
+/// `WhoisLeader` answers side-effect free leader queries.
+pub const WHOIS_LEADER: &str = "whois_leader";
+/// Messages are routed by `group_id`.
+pub const ELECTION_GROUPS: &str = "election_groups";
+/// `FollowEvents` streams the election event log.
+pub const FOLLOW_EVENTS: &str = "follow_events";
+
+/// Every capability described by this crate's `election.proto`. A node should advertise only
+/// the ones it implements, not this whole list.
+pub const ALL: &[&str] = &[SUBSCRIBE_COORDINATOR, WHOIS_LEADER, ELECTION_GROUPS, FOLLOW_EVENTS];
+
+/// Every capability described by this crate's `election.proto`, as sent on the wire.
+pub fn all() -> Vec<String> {
+	ALL.iter().map(|capability| capability.to_string()).collect()
+}
+
+/// Whether a peer's advertised `capabilities` include `capability`.
+pub fn supports(capabilities: &[String], capability: &str) -> bool {
+	capabilities.iter().any(|advertised| advertised == capability)
+}
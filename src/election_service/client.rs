@@ -0,0 +1,907 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Status, Streaming};
+
+use super::bully_client::BullyClient;
+use super::validate::check_addr;
+use super::{
+	Coordinator, Node, PingRequest, PingResponse, SubscribeCoordinatorRequest, WhoisLeaderRequest, WhoisLeaderResponse,
+};
+use crate::ServicesError;
+
+/// Asks every node in `nodes` for its leader view and returns the one a strict majority agrees on.
+///
+/// The majority is weighted by [`Node::effective_vote_weight`], so with default weights it is
+/// a plain node-count majority.
+///
+/// Nodes are queried concurrently. `timeout` bounds each connection attempt and the query
+/// itself, so a node with `k` addresses answers or fails within `(k + 1) * timeout`.
+/// Unreachable nodes, nodes that report no leader, and peers that predate `WhoisLeader` (they
+/// answer `Unimplemented`) count against the majority, so
+/// [`ServicesError::NoQuorum`] means the cluster could not be shown to agree, not that it has no leader.
+///
+/// Every node votes once: listing the same node id twice would count its vote twice, so it
+/// fails with [`ServicesError::Config`] before any node is asked.
+///
+/// ```
+/// use std::time::Duration;
+/// use services::election_service::{whois_majority_leader, Node, WhoisLeaderResponse};
+/// use services::testing::RecordingBully;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let leader = Node { id: 9, addr: "10.0.0.9:50051".to_string(), ..Default::default() };
+/// let view = WhoisLeaderResponse { leader: Some(leader), epoch: 4 };
+/// let addr = RecordingBully::new().with_whois_response(view.clone()).serve()?;
+/// let member = Node { addr: addr.to_string(), ..Default::default() };
+///
+/// let agreed = whois_majority_leader(&[member], "", Duration::from_secs(1)).await?;
+/// assert_eq!(agreed, view);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// A weighted minority can carry the majority on its own, and losing it loses the quorum:
+///
+/// ```
+/// use std::time::Duration;
+/// use services::election_service::{whois_majority_leader, Node, WhoisLeaderResponse};
+/// use services::testing::RecordingBully;
+/// use services::ServicesError;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let timeout = Duration::from_secs(1);
+/// let leader = Node { id: 9, addr: "10.0.0.9:50051".to_string(), ..Default::default() };
+/// let view = WhoisLeaderResponse { leader: Some(leader), epoch: 4 };
+/// let serve = || RecordingBully::new().with_whois_response(view.clone()).serve().map(|addr| addr.to_string());
+/// // Bound, then closed again: connecting is refused.
+/// let down = || std::net::TcpListener::bind("127.0.0.1:0")?.local_addr().map(|addr| addr.to_string());
+/// let node = |id: i64, addr: String, vote_weight: u32| Node { id, addr, vote_weight, ..Default::default() };
+///
+/// // 3 of 5 votes: the primary alone outweighs two unreachable secondaries.
+/// let cluster = [node(1, serve()?, 3), node(2, down()?, 1), node(3, down()?, 1)];
+/// assert_eq!(whois_majority_leader(&cluster, "", timeout).await?, view);
+///
+/// // 2 of 5 votes: the secondaries agree with each other, but not enough without the primary.
+/// let cluster = [node(1, down()?, 3), node(2, serve()?, 1), node(3, serve()?, 1)];
+/// let err = whois_majority_leader(&cluster, "", timeout).await.unwrap_err();
+/// assert!(matches!(err, ServicesError::NoQuorum { votes: 2, required: 3 }));
+///
+/// // Listing a secondary twice does not let it vote twice.
+/// let cluster = [node(1, down()?, 3), node(2, serve()?, 1), node(2, serve()?, 1)];
+/// let err = whois_majority_leader(&cluster, "", timeout).await.unwrap_err();
+/// assert!(matches!(err, ServicesError::Config(_)));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Cancellation safe: the queries run in a `JoinSet`, so dropping the future aborts every
+/// outstanding query, and no state outlives it.
+///
+/// ```
+/// use std::time::Duration;
+/// use services::election_service::{whois_majority_leader, Node};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let alive_tasks = || tokio::runtime::Handle::current().metrics().num_alive_tasks();
+/// let idle = alive_tasks();
+///
+/// // Accepts TCP connections but never answers, so every query stays in flight.
+/// let stalled = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+/// let addr = stalled.local_addr()?.to_string();
+/// let nodes = [1, 2, 3].map(|id| Node { id, addr: addr.clone(), ..Default::default() });
+/// let lookup = whois_majority_leader(&nodes, "", Duration::from_secs(30));
+/// assert!(tokio::time::timeout(Duration::from_millis(200), lookup).await.is_err());
+///
+/// // Cancelling the lookup took its queries and their connections down with it.
+/// tokio::time::timeout(Duration::from_secs(1), async {
+///     while alive_tasks() > idle {
+///         tokio::time::sleep(Duration::from_millis(10)).await;
+///     }
+/// })
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn whois_majority_leader(
+	nodes: &[Node],
+	group_id: &str,
+	timeout: Duration,
+) -> Result<WhoisLeaderResponse, ServicesError> {
+	let mut ids = HashSet::new();
+	if let Some(node) = nodes.iter().find(|node| !ids.insert(node.id)) {
+		return Err(ServicesError::Config(format!("node {} listed more than once", node.id)));
+	}
+	let report = cluster_consistency(nodes, group_id, timeout).await;
+	let required = nodes.iter().map(Node::effective_vote_weight).sum::<u64>() / 2 + 1;
+	let best = report.groups.into_iter().find(|group| group.view.leader.is_some());
+	match best {
+		Some(group) if group.votes() >= required => Ok(group.view),
+		best => Err(ServicesError::NoQuorum { votes: best.map_or(0, |group| group.votes()), required }),
+	}
+}
+
+/// Nodes that reported the same leader view to [`cluster_consistency`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewGroup {
+	pub view: WhoisLeaderResponse,
+	pub nodes: Vec<Node>,
+}
+
+impl ViewGroup {
+	/// Combined [`Node::effective_vote_weight`] of the nodes in this group.
+	pub fn votes(&self) -> u64 {
+		self.nodes.iter().map(Node::effective_vote_weight).sum()
+	}
+}
+
+/// Every node's leader view, grouped, as collected by [`cluster_consistency`].
+#[derive(Debug)]
+pub struct ConsistencyReport {
+	/// Distinct views, most votes first; ties keep the order of the nodes asked. A group whose
+	/// `view.leader` is `None` holds the nodes that know no leader.
+	pub groups: Vec<ViewGroup>,
+	/// Nodes that could not be asked, with the reason.
+	pub unreachable: Vec<(Node, ServicesError)>,
+}
+
+impl ConsistencyReport {
+	/// Whether every node answered and all of them report the same view.
+	pub fn is_consistent(&self) -> bool {
+		self.groups.len() == 1 && self.unreachable.is_empty()
+	}
+
+	/// The view to adopt once a partition heals: the single highest-epoch leader, provided nodes
+	/// holding a strict weighted majority of all nodes asked, unreachable ones included, report it.
+	///
+	/// `None` means there is no such view (the newest leader lacks a majority, or two leaders
+	/// share the newest epoch) and a fresh election is due.
+	///
+	/// ```
+	/// use services::election_service::{ConsistencyReport, Node, ViewGroup, WhoisLeaderResponse};
+	///
+	/// let group = |leader: i64, epoch: u64, members: std::ops::Range<i64>| ViewGroup {
+	///     view: WhoisLeaderResponse { leader: Some(Node { id: leader, ..Default::default() }), epoch },
+	///     nodes: members.map(|id| Node { id, ..Default::default() }).collect(),
+	/// };
+	///
+	/// // The partition healed: three nodes followed leader 3 at epoch 6, two still follow leader 1.
+	/// let healed = ConsistencyReport { groups: vec![group(3, 6, 3..6), group(1, 5, 1..3)], unreachable: Vec::new() };
+	/// assert_eq!(healed.reconciled_leader(), Some(&healed.groups[0].view));
+	///
+	/// // The newest leader only has a minority behind it: hold a fresh election instead.
+	/// let split = ConsistencyReport { groups: vec![group(1, 5, 1..4), group(3, 6, 4..6)], unreachable: Vec::new() };
+	/// assert_eq!(split.reconciled_leader(), None);
+	/// ```
+	pub fn reconciled_leader(&self) -> Option<&WhoisLeaderResponse> {
+		let leaders = || self.groups.iter().filter(|group| group.view.leader.is_some());
+		let newest = leaders().map(|group| group.view.epoch).max()?;
+		let mut at_newest = leaders().filter(|group| group.view.epoch == newest);
+		let group = at_newest.next()?;
+		if at_newest.next().is_some() {
+			return None;
+		}
+		let total = self.groups.iter().map(ViewGroup::votes).sum::<u64>()
+			+ self.unreachable.iter().map(|(node, _)| node.effective_vote_weight()).sum::<u64>();
+		(group.votes() > total / 2).then_some(&group.view)
+	}
+}
+
+/// Asks every node in `nodes` for its leader view and groups the nodes by the view they report,
+/// to diagnose a split brain.
+///
+/// Views are the same when they name the same leader id at the same epoch. Nodes are queried
+/// concurrently with the same `timeout` as [`whois_majority_leader`], which decides its
+/// majority from this report; peers that predate `WhoisLeader` show up as unreachable.
+///
+/// ```
+/// use std::time::Duration;
+/// use services::election_service::{cluster_consistency, Node, WhoisLeaderResponse};
+/// use services::testing::RecordingBully;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let view = |leader: i64, epoch: u64| WhoisLeaderResponse { leader: Some(Node { id: leader, ..Default::default() }), epoch };
+/// let serve = |view| RecordingBully::new().with_whois_response(view).serve().map(|addr| addr.to_string());
+/// let down = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?.to_string();
+/// let node = |id: i64, addr: String| Node { id, addr, ..Default::default() };
+///
+/// // Two sides of a partition each elected their own leader; a third node is unreachable.
+/// let cluster = [node(1, serve(view(1, 5))?), node(2, serve(view(2, 6))?), node(3, down)];
+/// let report = cluster_consistency(&cluster, "", Duration::from_secs(1)).await;
+///
+/// assert!(!report.is_consistent());
+/// let groups: Vec<_> = report.groups.iter().map(|group| (group.view.clone(), group.nodes.clone())).collect();
+/// assert_eq!(groups, [(view(1, 5), vec![cluster[0].clone()]), (view(2, 6), vec![cluster[1].clone()])]);
+/// assert_eq!(report.unreachable.len(), 1);
+/// assert_eq!(report.unreachable[0].0, cluster[2]);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn cluster_consistency(nodes: &[Node], group_id: &str, timeout: Duration) -> ConsistencyReport {
+	let mut queries = JoinSet::new();
+	for (index, node) in nodes.iter().enumerate() {
+		let query = whois_leader(node.clone(), group_id.to_string(), timeout);
+		queries.spawn(async move { (index, query.await) });
+	}
+	let mut answers: Vec<Option<Result<WhoisLeaderResponse, ServicesError>>> = nodes.iter().map(|_| None).collect();
+	while let Some(result) = queries.join_next().await {
+		if let Ok((index, answer)) = result {
+			answers[index] = Some(answer);
+		}
+	}
+
+	let mut report = ConsistencyReport { groups: Vec::new(), unreachable: Vec::new() };
+	for (node, answer) in nodes.iter().zip(answers) {
+		let view = match answer {
+			Some(Ok(view)) => view,
+			Some(Err(err)) => {
+				report.unreachable.push((node.clone(), err));
+				continue;
+			}
+			None => {
+				report.unreachable.push((node.clone(), ServicesError::Config("query task failed".to_string())));
+				continue;
+			}
+		};
+		match report.groups.iter_mut().find(|group| same_view(&group.view, &view)) {
+			Some(group) => group.nodes.push(node.clone()),
+			None => report.groups.push(ViewGroup { view, nodes: vec![node.clone()] }),
+		}
+	}
+	report.groups.sort_by_key(|group| std::cmp::Reverse(group.votes()));
+	report
+}
+
+async fn whois_leader(node: Node, group_id: String, timeout: Duration) -> Result<WhoisLeaderResponse, ServicesError> {
+	let (mut client, _) = connect_node(&node, Some(timeout)).await?;
+	let response = client.whois_leader(WhoisLeaderRequest { group_id }).await?;
+	Ok(response.into_inner())
+}
+
+/// Outcome of one [`ping_peer`] probe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PingSample {
+	/// Round trip of the `Ping` call alone, excluding connection setup.
+	pub rtt: Duration,
+	/// Local wall clock when the ping was sent.
+	pub sent_at: SystemTime,
+	pub response: PingResponse,
+}
+
+impl PingSample {
+	/// Estimated offset of the peer's clock from ours, in milliseconds, assuming the reply was
+	/// stamped halfway through the round trip. `None` when the peer did not report `server_time_ms`.
+	pub fn clock_offset_ms(&self) -> Option<i64> {
+		if self.response.server_time_ms == 0 {
+			return None;
+		}
+		let midpoint = (self.sent_at + self.rtt / 2).duration_since(UNIX_EPOCH).unwrap_or_default();
+		Some(self.response.server_time_ms - i64::try_from(midpoint.as_millis()).unwrap_or(i64::MAX))
+	}
+}
+
+/// Pings `node` and measures the round trip.
+///
+/// `timeout` bounds each connection attempt and the ping itself. The measured `rtt` covers only
+/// the `Ping` call, so it is comparable across probes whether or not connecting was slow.
+///
+/// ```
+/// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+/// use services::election_service::{ping_peer, Node, PingResponse};
+/// use services::testing::RecordingBully;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let now_ms = i64::try_from(SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis())?;
+/// let reply = PingResponse { alive: true, server_time_ms: now_ms, ..Default::default() };
+/// let addr = RecordingBully::new().with_ping_response(reply).serve()?;
+/// let node = Node { addr: addr.to_string(), ..Default::default() };
+///
+/// let sample = ping_peer(&node, Duration::from_secs(1)).await?;
+/// assert!(sample.response.alive);
+/// assert!(sample.rtt < Duration::from_secs(1));
+/// assert!(sample.clock_offset_ms().unwrap().abs() < 1_000);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn ping_peer(node: &Node, timeout: Duration) -> Result<PingSample, ServicesError> {
+	let (mut client, _) = connect_node(node, Some(timeout)).await?;
+	let sent_at = SystemTime::now();
+	let started = Instant::now();
+	let response = client.ping(PingRequest::default()).await?.into_inner();
+	Ok(PingSample { rtt: started.elapsed(), sent_at, response })
+}
+
+/// Delay before [`whois_leader_hedged`] asks the next node when the previous ones have not answered.
+pub const DEFAULT_HEDGE_DELAY: Duration = Duration::from_millis(50);
+/// Number of nodes [`whois_leader_hedged`] asks at most.
+pub const DEFAULT_HEDGE_FANOUT: usize = 2;
+
+/// How [`whois_leader_hedged`] spreads one lookup across nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hedge {
+	/// Wait this long for an answer before also asking the next node.
+	pub delay: Duration,
+	/// Upper bound on the number of nodes asked.
+	pub fanout: usize,
+	/// Bound on each connection attempt to a node and on the query itself.
+	pub timeout: Duration,
+}
+
+impl Default for Hedge {
+	fn default() -> Self {
+		Self { delay: DEFAULT_HEDGE_DELAY, fanout: DEFAULT_HEDGE_FANOUT, timeout: Duration::from_secs(1) }
+	}
+}
+
+/// Asks `nodes` in order for their leader view, hedging against slow nodes, and returns the first answer.
+///
+/// If no node has answered after `hedge.delay`, the next one is asked as well (a failed node is
+/// replaced right away), up to `hedge.fanout` nodes. The first answer wins and the remaining
+/// queries are aborted. Unlike [`whois_majority_leader`] this trusts a single node, trading
+/// agreement for tail latency. Fails with the last error when every asked node failed.
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use services::election_service::{whois_leader_hedged, Hedge, Node, WhoisLeaderResponse};
+/// use services::testing::RecordingBully;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // Accepts TCP connections but never speaks gRPC.
+/// let stalled = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+/// let view = WhoisLeaderResponse { epoch: 7, ..Default::default() };
+/// let nodes = [
+///     Node { addr: stalled.local_addr()?.to_string(), ..Default::default() },
+///     Node { addr: RecordingBully::new().with_whois_response(view.clone()).serve()?.to_string(), ..Default::default() },
+/// ];
+///
+/// let started = Instant::now();
+/// let hedge = Hedge { timeout: Duration::from_secs(10), ..Default::default() };
+/// assert_eq!(whois_leader_hedged(&nodes, "", hedge).await?, view);
+/// assert!(started.elapsed() < Duration::from_secs(5));
+/// # Ok(())
+/// # }
+/// ```
+pub async fn whois_leader_hedged(nodes: &[Node], group_id: &str, hedge: Hedge) -> Result<WhoisLeaderResponse, ServicesError> {
+	let mut candidates = nodes.iter().take(hedge.fanout.max(1));
+	let mut queries = JoinSet::new();
+	let mut last_err = ServicesError::Config("no nodes to ask for the leader".to_string());
+	loop {
+		if let Some(node) = candidates.next() {
+			queries.spawn(whois_leader(node.clone(), group_id.to_string(), hedge.timeout));
+		}
+		let more = candidates.len() > 0;
+		let answer = tokio::select! {
+			Some(answer) = queries.join_next() => Some(answer),
+			_ = tokio::time::sleep(hedge.delay), if more => None,
+			else => return Err(last_err),
+		};
+		match answer {
+			Some(Ok(Ok(view))) => return Ok(view),
+			Some(Ok(Err(err))) => last_err = err,
+			Some(Err(_)) | None => {}
+		}
+	}
+}
+
+/// Connects to `node` at the first address that accepts: `addr`, then each of `additional_addrs` in order.
+///
+/// Returns the client together with the index of the address that accepted: 0 for `addr`,
+/// `i + 1` for `additional_addrs[i]`. With a `timeout`, it bounds each connection attempt and
+/// every request made on the returned client. Fails with the error of the last address tried.
+///
+/// ```
+/// use services::election_service::{connect_node, Node, PingRequest};
+/// use services::testing::RecordingBully;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dead = tokio::net::TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+/// let live = RecordingBully::new().serve()?;
+/// let node = Node { addr: dead.to_string(), additional_addrs: vec![live.to_string()], ..Default::default() };
+///
+/// let (mut client, index) = connect_node(&node, None).await?;
+/// assert_eq!(index, 1);
+/// assert!(client.ping(PingRequest::default()).await?.into_inner().alive);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn connect_node(
+	node: &Node,
+	timeout: Option<Duration>,
+) -> Result<(BullyClient<Channel>, usize), ServicesError> {
+	connect(node, timeout, 0, |endpoint| endpoint).await
+}
+
+/// [`connect_node`], trying the address at index `first` before the others and applying
+/// `configure` to each address's endpoint before dialling.
+async fn connect(
+	node: &Node,
+	timeout: Option<Duration>,
+	first: usize,
+	configure: impl Fn(Endpoint) -> Endpoint,
+) -> Result<(BullyClient<Channel>, usize), ServicesError> {
+	let addrs: Vec<&String> = std::iter::once(&node.addr).chain(&node.additional_addrs).collect();
+	let order = (first < addrs.len()).then_some(first).into_iter().chain((0..addrs.len()).filter(|&index| index != first));
+	let mut last_err = ServicesError::Config(format!("node {} has no address", node.id));
+	for index in order.filter(|&index| !addrs[index].is_empty()) {
+		let connected = match endpoint(addrs[index], timeout) {
+			Ok(endpoint) => configure(endpoint).connect().await.map_err(ServicesError::from),
+			Err(err) => Err(err),
+		};
+		match connected {
+			Ok(channel) => return Ok((BullyClient::new(channel), index)),
+			Err(err) => last_err = err,
+		}
+	}
+	Err(last_err)
+}
+
+/// Builds the (unconnected) `Endpoint` the helpers in this module use to reach `node.addr`.
+///
+/// With a `timeout`, it applies to connecting and to every request. Exposed so callers that
+/// pool or warm connections themselves get exactly the same endpoint settings.
+///
+/// ```
+/// use std::time::Duration;
+/// use services::election_service::{endpoint_for, Node};
+///
+/// let node = Node { addr: "10.0.0.1:50051".to_string(), ..Default::default() };
+/// let endpoint = endpoint_for(&node, Some(Duration::from_secs(2)))?;
+/// assert_eq!(endpoint.uri(), "http://10.0.0.1:50051/");
+/// assert_eq!(endpoint.get_connect_timeout(), Some(Duration::from_secs(2)));
+/// # Ok::<(), services::ServicesError>(())
+/// ```
+pub fn endpoint_for(node: &Node, timeout: Option<Duration>) -> Result<Endpoint, ServicesError> {
+	endpoint(&node.addr, timeout)
+}
+
+fn endpoint(addr: &str, timeout: Option<Duration>) -> Result<Endpoint, ServicesError> {
+	check_addr(addr).map_err(|reason| ServicesError::Config(format!("invalid node address {addr:?}: {reason}")))?;
+	let endpoint = Endpoint::from_shared(format!("http://{addr}"))
+		.map_err(|err| ServicesError::Config(format!("invalid node address {addr:?}: {err}")))?;
+	Ok(match timeout {
+		Some(timeout) => endpoint.connect_timeout(timeout).timeout(timeout),
+		None => endpoint,
+	})
+}
+
+fn same_view(a: &WhoisLeaderResponse, b: &WhoisLeaderResponse) -> bool {
+	let id = |view: &WhoisLeaderResponse| view.leader.as_ref().map(|leader| leader.id);
+	a.epoch == b.epoch && id(a) == id(b)
+}
+
+/// First delay before [`follow_coordinator`] reconnects after its stream breaks.
+pub const DEFAULT_RECONNECT_INITIAL: Duration = Duration::from_millis(100);
+/// Ceiling the reconnect delay doubles up to.
+pub const DEFAULT_RECONNECT_MAX: Duration = Duration::from_secs(5);
+/// Bound on each step of a [`follow_coordinator`] reconnect attempt.
+pub const DEFAULT_RECONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+/// Interval of the HTTP/2 keepalive pings [`follow_coordinator`] sends on an open subscription.
+pub const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+/// Shortest reconnect delay [`follow_coordinator`] waits; a smaller `initial` is raised to it.
+pub const MIN_RECONNECT_DELAY: Duration = Duration::from_millis(1);
+
+/// How [`follow_coordinator`] reconnects: the delay between attempts doubles from `initial`
+/// (at least [`MIN_RECONNECT_DELAY`]) up to `max`, and each connect and each request made while
+/// resubscribing gets at most `timeout`.
+///
+/// ```
+/// use services::election_service::{
+///     ReconnectBackoff, DEFAULT_KEEPALIVE_INTERVAL, DEFAULT_RECONNECT_INITIAL, DEFAULT_RECONNECT_MAX,
+///     DEFAULT_RECONNECT_TIMEOUT,
+/// };
+///
+/// let backoff = ReconnectBackoff::default();
+/// assert_eq!((backoff.initial, backoff.max), (DEFAULT_RECONNECT_INITIAL, DEFAULT_RECONNECT_MAX));
+/// assert_eq!((backoff.timeout, backoff.keepalive), (DEFAULT_RECONNECT_TIMEOUT, DEFAULT_KEEPALIVE_INTERVAL));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectBackoff {
+	pub initial: Duration,
+	pub max: Duration,
+	/// Applies to connecting to each address, to opening the subscription and to each
+	/// keepalive ping, not to the subscription itself, which stays open as long as the node
+	/// keeps it up.
+	pub timeout: Duration,
+	/// Interval of HTTP/2 keepalive pings on the open subscription. A ping left unanswered for
+	/// `timeout` drops the connection, so a node that vanishes without closing it is noticed
+	/// within `keepalive + timeout`. `Duration::ZERO` disables keepalives.
+	pub keepalive: Duration,
+}
+
+impl Default for ReconnectBackoff {
+	fn default() -> Self {
+		Self {
+			initial: DEFAULT_RECONNECT_INITIAL,
+			max: DEFAULT_RECONNECT_MAX,
+			timeout: DEFAULT_RECONNECT_TIMEOUT,
+			keepalive: DEFAULT_KEEPALIVE_INTERVAL,
+		}
+	}
+}
+
+/// Follows leader changes announced by `node`, reconnecting whenever the stream breaks.
+///
+/// Runs `SubscribeCoordinator` on a background task and never surfaces connection errors: after a
+/// break it waits according to `backoff`, resubscribes, and first re-emits the node's current
+/// leader (via `WhoisLeader`) so nothing missed while disconnected goes unnoticed. Consumers
+/// should therefore expect repeats of the latest announcement. Peers that answer `WhoisLeader`
+/// with `Unimplemented` are still followed, just without the replay. The open subscription is
+/// probed with HTTP/2 keepalive pings (see [`ReconnectBackoff::keepalive`]), so a node that
+/// vanishes silently also counts as a break. Must be called from within a tokio runtime.
+///
+/// ```
+/// use services::election_service::{follow_coordinator, Node, ReconnectBackoff, SubscribeCoordinatorRequest, WhoisLeaderResponse};
+/// use services::testing::RecordingBully;
+/// use tokio_stream::StreamExt;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // Reserve a port, but leave the node down while the follower starts.
+/// let addr = tokio::net::TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+/// let node = Node { addr: addr.to_string(), ..Default::default() };
+/// let mut leaders = follow_coordinator(node, SubscribeCoordinatorRequest::default(), ReconnectBackoff::default());
+///
+/// let leader = Node { id: 9, addr: "10.0.0.9:50051".to_string(), ..Default::default() };
+/// let mock = RecordingBully::new().with_whois_response(WhoisLeaderResponse { leader: Some(leader.clone()), epoch: 2 });
+/// mock.serve_on(addr)?;
+///
+/// // Once the node comes up the follower connects and replays its current leader.
+/// let announcement = leaders.next().await.unwrap();
+/// assert_eq!((announcement.leader, announcement.epoch), (Some(leader), 2));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// When the node restarts mid-stream, the follower resubscribes and keeps delivering:
+///
+/// ```
+/// use std::net::SocketAddr;
+/// use std::time::Duration;
+/// use services::election_service::{follow_coordinator, Coordinator, Node, ReconnectBackoff, SubscribeCoordinatorRequest, WhoisLeaderResponse};
+/// use services::testing::RecordingBully;
+/// use tokio_stream::StreamExt;
+///
+/// // Serves `mock` on its own runtime, so dropping the runtime kills the node, connections included.
+/// fn start(mock: RecordingBully, addr: SocketAddr) -> std::io::Result<tokio::runtime::Runtime> {
+///     let node = tokio::runtime::Runtime::new()?;
+///     let _runtime = node.enter();
+///     mock.serve_on(addr)?;
+///     Ok(node)
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let leader = |id: i64, epoch: u64| Coordinator { leader: Some(Node { id, ..Default::default() }), epoch, ..Default::default() };
+/// let whois = |c: Coordinator| WhoisLeaderResponse { leader: c.leader, epoch: c.epoch };
+/// let addr = tokio::net::TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+///
+/// let before = RecordingBully::new().with_whois_response(whois(leader(1, 1)));
+/// let node = start(before.clone(), addr)?;
+/// let backoff = ReconnectBackoff { initial: Duration::from_millis(20), ..Default::default() };
+/// let follow = Node { addr: addr.to_string(), ..Default::default() };
+/// let mut leaders = follow_coordinator(follow, SubscribeCoordinatorRequest::default(), backoff);
+/// assert_eq!(leaders.next().await, Some(leader(1, 1)));
+/// before.publish_coordinator(leader(2, 2));
+/// assert_eq!(leaders.next().await, Some(leader(2, 2)));
+///
+/// // Kill the node and bring it back up on the same address with a newer leader.
+/// tokio::task::spawn_blocking(move || drop(node)).await?;
+/// let after = RecordingBully::new().with_whois_response(whois(leader(3, 3)));
+/// let node = start(after.clone(), addr)?;
+///
+/// // The follower never sees the break: it replays the new leader, then streams again.
+/// assert_eq!(leaders.next().await, Some(leader(3, 3)));
+/// after.publish_coordinator(leader(4, 4));
+/// assert_eq!(leaders.next().await, Some(leader(4, 4)));
+/// node.shutdown_background();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// A node that goes silent without closing the connection is caught by the keepalive pings:
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::time::Duration;
+/// use services::election_service::{follow_coordinator, Node, ReconnectBackoff, SubscribeCoordinatorRequest, WhoisLeaderResponse};
+/// use services::testing::RecordingBully;
+/// use tokio::io::{AsyncReadExt, AsyncWriteExt};
+/// use tokio_stream::StreamExt;
+///
+/// // Copies bytes until its connection's generation is frozen, then holds the socket open in silence.
+/// async fn pipe(mut from: tokio::net::tcp::OwnedReadHalf, mut to: tokio::net::tcp::OwnedWriteHalf, frozen: Arc<AtomicUsize>, generation: usize) {
+///     let mut buf = [0; 4096];
+///     while let Ok(n @ 1..) = from.read(&mut buf).await {
+///         if frozen.load(Ordering::SeqCst) > generation {
+///             std::future::pending::<()>().await;
+///         }
+///         if to.write_all(&buf[..n]).await.is_err() {
+///             return;
+///         }
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let view = WhoisLeaderResponse { leader: Some(Node { id: 9, ..Default::default() }), epoch: 2 };
+/// let upstream = RecordingBully::new().with_whois_response(view.clone()).serve()?;
+///
+/// // A proxy in front of the node; bumping `frozen` silences every connection open so far.
+/// let proxy = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+/// let node = Node { addr: proxy.local_addr()?.to_string(), ..Default::default() };
+/// let frozen = Arc::new(AtomicUsize::new(0));
+/// let gate = frozen.clone();
+/// tokio::spawn(async move {
+///     while let Ok((client, _)) = proxy.accept().await {
+///         let Ok(server) = tokio::net::TcpStream::connect(upstream).await else { return };
+///         let generation = gate.load(Ordering::SeqCst);
+///         let ((client_rx, client_tx), (server_rx, server_tx)) = (client.into_split(), server.into_split());
+///         tokio::spawn(pipe(client_rx, server_tx, gate.clone(), generation));
+///         tokio::spawn(pipe(server_rx, client_tx, gate.clone(), generation));
+///     }
+/// });
+///
+/// let backoff = ReconnectBackoff {
+///     timeout: Duration::from_millis(200),
+///     keepalive: Duration::from_millis(200),
+///     ..Default::default()
+/// };
+/// let mut leaders = follow_coordinator(node, SubscribeCoordinatorRequest::default(), backoff);
+/// let first = leaders.next().await.unwrap();
+/// assert_eq!((first.leader, first.epoch), (view.leader.clone(), view.epoch));
+///
+/// // Silence the subscription: an unanswered keepalive drops it, and the follower resubscribes
+/// // through a fresh connection, replaying the leader.
+/// frozen.fetch_add(1, Ordering::SeqCst);
+/// let replayed = tokio::time::timeout(Duration::from_secs(3), leaders.next()).await?.unwrap();
+/// assert_eq!((replayed.leader, replayed.epoch), (view.leader, view.epoch));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// A peer that streams announcements but predates `WhoisLeader` is followed without the replay:
+///
+/// ```
+/// use std::time::Duration;
+/// use services::election_service::bully_server::{Bully, BullyServer};
+/// use services::election_service::{follow_coordinator, Coordinator, CoordinatorStream, Node, ReconnectBackoff};
+/// use services::election_service::{ElectionRequest, ElectionResponse, PingRequest, PingResponse, SubscribeCoordinatorRequest};
+/// use tokio_stream::StreamExt;
+/// use tonic::{Request, Response, Status};
+///
+/// // Implements the original RPCs and SubscribeCoordinator, so WhoisLeader answers Unimplemented.
+/// struct StreamOnly(Coordinator);
+///
+/// #[tonic::async_trait]
+/// impl Bully for StreamOnly {
+///     async fn election(&self, _: Request<ElectionRequest>) -> Result<Response<ElectionResponse>, Status> {
+///         Ok(Response::new(ElectionResponse::default()))
+///     }
+///     async fn announce_coordinator(&self, _: Request<Coordinator>) -> Result<Response<PingResponse>, Status> {
+///         Ok(Response::new(PingResponse::default()))
+///     }
+///     async fn notify_coordinator(&self, _: Request<Coordinator>) -> Result<Response<PingResponse>, Status> {
+///         Ok(Response::new(PingResponse::default()))
+///     }
+///     async fn ping(&self, _: Request<PingRequest>) -> Result<Response<PingResponse>, Status> {
+///         Ok(Response::new(PingResponse { alive: true, ..Default::default() }))
+///     }
+///     async fn subscribe_coordinator(
+///         &self,
+///         _: Request<SubscribeCoordinatorRequest>,
+///     ) -> Result<Response<CoordinatorStream>, Status> {
+///         // Announce once, then keep the stream open.
+///         let stream = tokio_stream::iter([Ok(self.0.clone())]).chain(tokio_stream::pending());
+///         Ok(Response::new(Box::pin(stream)))
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let announcement = Coordinator { leader: Some(Node { id: 9, ..Default::default() }), epoch: 2, ..Default::default() };
+/// let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+/// let node = Node { addr: listener.local_addr()?.to_string(), ..Default::default() };
+/// let incoming = tonic::transport::server::TcpIncoming::from(listener);
+/// let server = tonic::transport::Server::builder().add_service(BullyServer::new(StreamOnly(announcement.clone())));
+/// tokio::spawn(server.serve_with_incoming(incoming));
+///
+/// let mut leaders = follow_coordinator(node, SubscribeCoordinatorRequest::default(), ReconnectBackoff::default());
+/// let first = tokio::time::timeout(Duration::from_secs(2), leaders.next()).await?;
+/// assert_eq!(first, Some(announcement));
+/// // The subscription stays up: no resubscribe replays the announcement.
+/// assert!(tokio::time::timeout(Duration::from_millis(300), leaders.next()).await.is_err());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// A primary address that silently drops connection attempts costs one `backoff.timeout`
+/// before `additional_addrs` are tried. The follower remembers which address worked and dials
+/// it first when it reconnects, so the dead primary is not waited on again:
+///
+/// ```
+/// use std::net::SocketAddr;
+/// use std::time::{Duration, Instant};
+/// use services::election_service::{follow_coordinator, Node, ReconnectBackoff, SubscribeCoordinatorRequest, WhoisLeaderResponse};
+/// use services::testing::RecordingBully;
+/// use tokio_stream::StreamExt;
+///
+/// fn start(mock: RecordingBully, addr: SocketAddr) -> std::io::Result<tokio::runtime::Runtime> {
+///     let node = tokio::runtime::Runtime::new()?;
+///     let _runtime = node.enter();
+///     mock.serve_on(addr)?;
+///     Ok(node)
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // Only Linux silently drops connection attempts to a listener whose accept backlog is full;
+/// // elsewhere they may be refused, which would not exercise the timeout.
+/// if !cfg!(target_os = "linux") {
+///     return Ok(());
+/// }
+/// let socket = tokio::net::TcpSocket::new_v4()?;
+/// socket.bind("127.0.0.1:0".parse()?)?;
+/// let blackholed = socket.listen(0)?;
+/// let _queued = tokio::net::TcpStream::connect(blackholed.local_addr()?).await?;
+///
+/// let view = |epoch| WhoisLeaderResponse { leader: Some(Node { id: 9, ..Default::default() }), epoch };
+/// let live = tokio::net::TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+/// let secondary = start(RecordingBully::new().with_whois_response(view(2)), live)?;
+/// let node = Node {
+///     addr: blackholed.local_addr()?.to_string(),
+///     additional_addrs: vec![live.to_string()],
+///     ..Default::default()
+/// };
+///
+/// let backoff = ReconnectBackoff { timeout: Duration::from_secs(1), ..Default::default() };
+/// let mut leaders = follow_coordinator(node, SubscribeCoordinatorRequest::default(), backoff);
+/// let announcement = tokio::time::timeout(Duration::from_secs(3), leaders.next()).await?.unwrap();
+/// assert_eq!(announcement.epoch, 2);
+///
+/// // Restart the secondary: the follower goes straight back to it.
+/// tokio::task::spawn_blocking(move || drop(secondary)).await?;
+/// let restarted = Instant::now();
+/// let secondary = start(RecordingBully::new().with_whois_response(view(3)), live)?;
+/// let announcement = tokio::time::timeout(Duration::from_secs(3), leaders.next()).await?.unwrap();
+/// assert_eq!(announcement.epoch, 3);
+/// assert!(restarted.elapsed() < backoff.timeout);
+/// secondary.shutdown_background();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Dropping the returned stream is how to cancel: the background task stops right away,
+/// whether it is connecting, resubscribing, waiting on the peer or backing off, and closes its
+/// connection.
+///
+/// ```
+/// use std::time::Duration;
+/// use services::election_service::{follow_coordinator, Node, ReconnectBackoff, SubscribeCoordinatorRequest};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let alive_tasks = || tokio::runtime::Handle::current().metrics().num_alive_tasks();
+/// let idle = alive_tasks();
+///
+/// // Accepts TCP connections but never answers, so the follower hangs while subscribing.
+/// let stalled = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+/// let node = Node { addr: stalled.local_addr()?.to_string(), ..Default::default() };
+/// let backoff = ReconnectBackoff { timeout: Duration::from_secs(30), ..Default::default() };
+/// let leaders = follow_coordinator(node, SubscribeCoordinatorRequest::default(), backoff);
+/// tokio::time::sleep(Duration::from_millis(200)).await;
+/// assert!(alive_tasks() > idle);
+///
+/// drop(leaders);
+/// tokio::time::timeout(Duration::from_secs(1), async {
+///     while alive_tasks() > idle {
+///         tokio::time::sleep(Duration::from_millis(10)).await;
+///     }
+/// })
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "following stops as soon as the returned stream is dropped"]
+pub fn follow_coordinator(
+	node: Node,
+	request: SubscribeCoordinatorRequest,
+	backoff: ReconnectBackoff,
+) -> ReceiverStream<Coordinator> {
+	let (tx, rx) = mpsc::channel(16);
+	tokio::spawn(async move {
+		let initial = backoff.initial.max(MIN_RECONNECT_DELAY);
+		let mut delay = initial;
+		let mut first = 0;
+		loop {
+			let subscribed = tokio::select! {
+				_ = tx.closed() => return,
+				subscribed = subscribe(&node, &request, backoff, first) => subscribed,
+			};
+			if let Ok(mut stream) = subscribed {
+				delay = initial;
+				first = stream.addr_index;
+				loop {
+					let next = tokio::select! {
+						_ = tx.closed() => return,
+						next = stream.message() => next,
+					};
+					let Ok(Some(announcement)) = next else { break };
+					if tx.send(announcement).await.is_err() {
+						return;
+					}
+				}
+			}
+			tokio::select! {
+				_ = tx.closed() => return,
+				_ = tokio::time::sleep(delay) => {}
+			}
+			delay = (delay * 2).min(backoff.max);
+		}
+	});
+	ReceiverStream::new(rx)
+}
+
+/// Opens a subscription, dialling the address at index `first` before the others, and returns it
+/// together with the node's current leader, if any.
+async fn subscribe(
+	node: &Node,
+	request: &SubscribeCoordinatorRequest,
+	backoff: ReconnectBackoff,
+	first: usize,
+) -> Result<CatchUp, ServicesError> {
+	let keepalive = |endpoint: Endpoint| match backoff.keepalive {
+		Duration::ZERO => endpoint,
+		// Without `while_idle`, hyper never pings a connection whose only request is an open
+		// stream waiting on the server.
+		interval => endpoint
+			.http2_keep_alive_interval(interval)
+			.keep_alive_timeout(backoff.timeout)
+			.keep_alive_while_idle(true),
+	};
+	let (mut client, addr_index) = connect(node, Some(backoff.timeout), first, keepalive).await?;
+	let stream = client.subscribe_coordinator(request.clone()).await?.into_inner();
+	let current = match client.whois_leader(WhoisLeaderRequest { group_id: request.group_id.clone() }).await {
+		Ok(view) => {
+			let view = view.into_inner();
+			view.leader.map(|leader| Coordinator {
+				leader: Some(leader),
+				epoch: view.epoch,
+				group_id: request.group_id.clone(),
+				..Default::default()
+			})
+		}
+		// A peer that streams but predates WhoisLeader just has nothing to replay.
+		Err(status) if status.code() == Code::Unimplemented => None,
+		Err(status) => return Err(status.into()),
+	};
+	Ok(CatchUp { current, stream, addr_index })
+}
+
+/// A fresh subscription that first replays the leader known at subscribe time.
+struct CatchUp {
+	current: Option<Coordinator>,
+	stream: Streaming<Coordinator>,
+	/// Address of the node the subscription runs over, as indexed by [`connect_node`].
+	addr_index: usize,
+}
+
+impl CatchUp {
+	async fn message(&mut self) -> Result<Option<Coordinator>, Status> {
+		match self.current.take() {
+			Some(current) => Ok(Some(current)),
+			None => self.stream.message().await,
+		}
+	}
+}
@@ -0,0 +1,116 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Suspicion level above which [`PhiAccrual`] considers a peer down. 8 means roughly a one in
+/// 10^8 chance that a live peer's heartbeat is merely this late.
+pub const DEFAULT_PHI_THRESHOLD: f64 = 8.0;
+/// Inter-arrival times a [`PhiAccrual`] keeps by default.
+pub const DEFAULT_PHI_WINDOW: usize = 100;
+/// Floor on the standard deviation, so perfectly regular heartbeats don't make the detector
+/// trip on the first millisecond of jitter.
+pub const DEFAULT_PHI_MIN_STD_DEV: Duration = Duration::from_millis(10);
+
+/// Phi-accrual failure detector (Hayashibara et al.) over the arrival times of a peer's
+/// heartbeats, such as successful pings.
+///
+/// Instead of a fixed number of missed pings, it keeps the last `window` inter-arrival times and
+/// reports how unlikely the current silence is under their normal distribution, as
+/// `phi = -log10(P(a heartbeat arrives this late))`. Slow or jittery links widen the
+/// distribution and so raise the time to trip, without making a steady link slow to react.
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use services::election_service::PhiAccrual;
+///
+/// let start = Instant::now();
+/// let at = |ms: u64| start + Duration::from_millis(ms);
+///
+/// // Heartbeats 90ms and 110ms apart: mean 100ms, standard deviation 10ms.
+/// let mut detector = PhiAccrual::default();
+/// let mut last = 0;
+/// detector.record_heartbeat(at(last));
+/// for gap in [90, 110].repeat(5) {
+///     last += gap;
+///     detector.record_heartbeat(at(last));
+/// }
+///
+/// // The default threshold of 8 trips a little over five deviations past the mean.
+/// let down_after = (100..300).find(|&ms| !detector.is_available(at(last + ms)));
+/// assert_eq!(down_after, Some(153));
+/// assert!(detector.phi(at(last + 100)) < 1.0);
+///
+/// // A heartbeat clears the suspicion.
+/// detector.record_heartbeat(at(last + 160));
+/// assert!(detector.is_available(at(last + 200)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PhiAccrual {
+	threshold: f64,
+	window: usize,
+	min_std_dev: Duration,
+	intervals: VecDeque<Duration>,
+	last_heartbeat: Option<Instant>,
+}
+
+impl Default for PhiAccrual {
+	fn default() -> Self {
+		Self::new(DEFAULT_PHI_THRESHOLD, DEFAULT_PHI_WINDOW)
+	}
+}
+
+impl PhiAccrual {
+	/// Trips above `threshold`, judging from the last `window` inter-arrival times (at least one).
+	pub fn new(threshold: f64, window: usize) -> Self {
+		let window = window.max(1);
+		Self {
+			threshold,
+			window,
+			min_std_dev: DEFAULT_PHI_MIN_STD_DEV,
+			intervals: VecDeque::with_capacity(window),
+			last_heartbeat: None,
+		}
+	}
+
+	/// Replaces [`DEFAULT_PHI_MIN_STD_DEV`] as the floor on the standard deviation.
+	#[must_use]
+	pub fn with_min_std_dev(mut self, min_std_dev: Duration) -> Self {
+		self.min_std_dev = min_std_dev;
+		self
+	}
+
+	/// Records a heartbeat that arrived `at`. Heartbeats must be recorded in order; one older than
+	/// the last is ignored.
+	pub fn record_heartbeat(&mut self, at: Instant) {
+		if let Some(last) = self.last_heartbeat {
+			let Some(interval) = at.checked_duration_since(last) else { return };
+			if self.intervals.len() == self.window {
+				self.intervals.pop_front();
+			}
+			self.intervals.push_back(interval);
+		}
+		self.last_heartbeat = Some(at);
+	}
+
+	/// Suspicion that the peer is down at `now`; 0 until two heartbeats have been recorded.
+	pub fn phi(&self, now: Instant) -> f64 {
+		let Some(last) = self.last_heartbeat else { return 0.0 };
+		if self.intervals.is_empty() {
+			return 0.0;
+		}
+		let samples = self.intervals.iter().map(Duration::as_secs_f64);
+		let mean = samples.clone().sum::<f64>() / self.intervals.len() as f64;
+		let variance = samples.map(|sample| (sample - mean).powi(2)).sum::<f64>() / self.intervals.len() as f64;
+		let std_dev = variance.sqrt().max(self.min_std_dev.as_secs_f64());
+
+		let elapsed = now.saturating_duration_since(last).as_secs_f64();
+		// Logistic approximation of the normal CDF, as used by Akka and Cassandra.
+		let y = (elapsed - mean) / std_dev;
+		let e = (-y * (1.5976 + 0.070566 * y * y)).exp();
+		if elapsed > mean { -(e / (1.0 + e)).log10() } else { -(1.0 - 1.0 / (1.0 + e)).log10() }
+	}
+
+	/// Whether the peer still counts as up at `now`, i.e. `phi(now)` is below the threshold.
+	pub fn is_available(&self, now: Instant) -> bool {
+		self.phi(now) < self.threshold
+	}
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tokio_stream::Stream;
+use tonic::{Code, Request, Response, Status};
+
+use super::bully_server::{Bully, BullyServer};
+use super::capabilities;
+use super::{
+	Coordinator, ElectionEvent, ElectionRequest, ElectionResponse, FollowEventsRequest, PingRequest, PingResponse,
+	SubscribeCoordinatorRequest, WhoisLeaderRequest, WhoisLeaderResponse,
+};
+
+/// Stream returned by `subscribe_coordinator`.
+pub type CoordinatorStream = Pin<Box<dyn Stream<Item = Result<Coordinator, Status>> + Send + 'static>>;
+/// Stream returned by `follow_events`.
+pub type ElectionEventStream = Pin<Box<dyn Stream<Item = Result<ElectionEvent, Status>> + Send + 'static>>;
+
+type GroupService = dyn Bully;
+type Observer = dyn Fn(&RpcInfo) + Send + Sync;
+
+/// One inbound RPC as seen by a [`MultiGroupElectionService::with_observer`] callback.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RpcInfo {
+	/// RPC name as declared in the proto, e.g. `"Ping"`.
+	pub method: &'static str,
+	/// Election group the request was routed by.
+	pub group_id: String,
+	/// Peer address, when the transport knows it.
+	pub remote_addr: Option<SocketAddr>,
+	/// `Code::Ok` on success, otherwise the code of the returned status. For streaming RPCs this
+	/// covers opening the stream only.
+	pub code: Code,
+}
+
+impl RpcInfo {
+	fn of<T>(method: &'static str, request: &Request<T>, group_id: &str) -> Self {
+		Self { method, group_id: group_id.to_owned(), remote_addr: request.remote_addr(), code: Code::Ok }
+	}
+}
+
+/// `Bully` service hosting several independent election groups behind one endpoint.
+///
+/// Every incoming RPC is routed by its `group_id` to the service registered for that
+/// group; unknown groups are rejected with `Status::not_found`. Peers that predate
+/// `group_id` send an empty id, so register the legacy cluster under `""`. Ping replies
+/// advertise [`capabilities::ELECTION_GROUPS`] on top of what the group's service advertises.
+///
+/// ```
+/// use services::election_service::bully_server::Bully;
+/// use services::election_service::{capabilities, Coordinator, MultiGroupElectionService, Node, PingRequest};
+/// use services::testing::{RecordedCall, RecordingBully};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), tonic::Status> {
+/// let (shard_a, shard_b) = (RecordingBully::new(), RecordingBully::new());
+/// let service = MultiGroupElectionService::new()
+///     .with_group("shard-a", shard_a.clone())
+///     .with_group("shard-b", shard_b.clone());
+///
+/// let leader = Node { id: 7, addr: "10.0.0.7:50051".to_string(), ..Default::default() };
+/// let announcement = Coordinator { leader: Some(leader), group_id: "shard-a".to_string(), ..Default::default() };
+/// service.announce_coordinator(tonic::Request::new(announcement.clone())).await?;
+///
+/// assert_eq!(shard_a.calls(), vec![RecordedCall::AnnounceCoordinator(announcement)]);
+/// assert!(shard_b.calls().is_empty());
+///
+/// let probe = PingRequest { group_id: "shard-b".to_string(), ..Default::default() };
+/// let reply = service.ping(tonic::Request::new(probe)).await?.into_inner();
+/// assert!(capabilities::supports(&reply.capabilities, capabilities::ELECTION_GROUPS));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct MultiGroupElectionService {
+	groups: HashMap<String, Arc<GroupService>>,
+	observer: Option<Arc<Observer>>,
+}
+
+impl MultiGroupElectionService {
+	/// Creates a service with no groups registered.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Registers the service handling `group_id`, replacing any previous one.
+	#[must_use]
+	pub fn with_group(mut self, group_id: impl Into<String>, service: impl Bully) -> Self {
+		self.groups.insert(group_id.into(), Arc::new(service));
+		self
+	}
+
+	/// Calls `observer` once for every inbound RPC, after it has been handled, replacing any previous observer.
+	///
+	/// The callback runs inline on the request path, so it must not block: record into a
+	/// counter or push onto a channel and do anything slow elsewhere.
+	///
+	/// ```
+	/// use std::sync::{Arc, Mutex};
+	///
+	/// use services::election_service::bully_server::Bully;
+	/// use services::election_service::{MultiGroupElectionService, PingRequest};
+	/// use services::testing::RecordingBully;
+	///
+	/// # #[tokio::main]
+	/// # async fn main() {
+	/// let observed = Arc::new(Mutex::new(Vec::new()));
+	/// let log = observed.clone();
+	/// let service = MultiGroupElectionService::new()
+	///     .with_group("", RecordingBully::new())
+	///     .with_observer(move |rpc| log.lock().unwrap().push((rpc.method, rpc.code)));
+	///
+	/// service.ping(tonic::Request::new(PingRequest::default())).await.unwrap();
+	/// service.ping(tonic::Request::new(PingRequest::default())).await.unwrap();
+	/// let unknown = PingRequest { group_id: "shard-x".to_string(), ..Default::default() };
+	/// service.ping(tonic::Request::new(unknown)).await.unwrap_err();
+	///
+	/// assert_eq!(
+	///     *observed.lock().unwrap(),
+	///     vec![("Ping", tonic::Code::Ok), ("Ping", tonic::Code::Ok), ("Ping", tonic::Code::NotFound)]
+	/// );
+	/// # }
+	/// ```
+	#[must_use]
+	pub fn with_observer(mut self, observer: impl Fn(&RpcInfo) + Send + Sync + 'static) -> Self {
+		self.observer = Some(Arc::new(observer));
+		self
+	}
+
+	/// Wraps the router in the generated tonic server, ready for `Server::builder().add_service(...)`.
+	///
+	/// ```no_run
+	/// use services::election_service::MultiGroupElectionService;
+	/// use services::testing::RecordingBully;
+	///
+	/// # #[tokio::main]
+	/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+	/// let elections = MultiGroupElectionService::new().with_group("", RecordingBully::new());
+	/// tonic::transport::Server::builder()
+	///     .add_service(elections.into_server())
+	///     .serve("0.0.0.0:50051".parse()?)
+	///     .await?;
+	/// # Ok(())
+	/// # }
+	/// ```
+	#[must_use]
+	pub fn into_server(self) -> BullyServer<Self> {
+		BullyServer::new(self)
+	}
+
+	/// Ids of all registered groups.
+	pub fn group_ids(&self) -> impl Iterator<Item = &str> {
+		self.groups.keys().map(String::as_str)
+	}
+
+	fn route(&self, group_id: &str) -> Result<Arc<GroupService>, Status> {
+		self.groups
+			.get(group_id)
+			.cloned()
+			.ok_or_else(|| Status::not_found(format!("unknown election group {group_id:?}")))
+	}
+
+	fn observe<T>(&self, mut info: RpcInfo, result: Result<Response<T>, Status>) -> Result<Response<T>, Status> {
+		if let Some(observer) = &self.observer {
+			info.code = result.as_ref().err().map_or(Code::Ok, Status::code);
+			observer(&info);
+		}
+		result
+	}
+}
+
+#[tonic::async_trait]
+impl Bully for MultiGroupElectionService {
+	async fn election(&self, request: Request<ElectionRequest>) -> Result<Response<ElectionResponse>, Status> {
+		let info = RpcInfo::of("Election", &request, &request.get_ref().group_id);
+		let result = async { self.route(&info.group_id)?.election(request).await }.await;
+		self.observe(info, result)
+	}
+
+	async fn announce_coordinator(&self, request: Request<Coordinator>) -> Result<Response<PingResponse>, Status> {
+		let info = RpcInfo::of("AnnounceCoordinator", &request, &request.get_ref().group_id);
+		let result = async { self.route(&info.group_id)?.announce_coordinator(request).await }.await;
+		self.observe(info, result)
+	}
+
+	async fn notify_coordinator(&self, request: Request<Coordinator>) -> Result<Response<PingResponse>, Status> {
+		let info = RpcInfo::of("NotifyCoordinator", &request, &request.get_ref().group_id);
+		let result = async { self.route(&info.group_id)?.notify_coordinator(request).await }.await;
+		self.observe(info, result)
+	}
+
+	async fn ping(&self, request: Request<PingRequest>) -> Result<Response<PingResponse>, Status> {
+		let info = RpcInfo::of("Ping", &request, &request.get_ref().group_id);
+		let result = async {
+			let mut response = self.route(&info.group_id)?.ping(request).await?;
+			let advertised = &mut response.get_mut().capabilities;
+			if !capabilities::supports(advertised, capabilities::ELECTION_GROUPS) {
+				advertised.push(capabilities::ELECTION_GROUPS.to_string());
+			}
+			Ok(response)
+		}
+		.await;
+		self.observe(info, result)
+	}
+
+	async fn subscribe_coordinator(
+		&self,
+		request: Request<SubscribeCoordinatorRequest>,
+	) -> Result<Response<CoordinatorStream>, Status> {
+		let info = RpcInfo::of("SubscribeCoordinator", &request, &request.get_ref().group_id);
+		let result = async { self.route(&info.group_id)?.subscribe_coordinator(request).await }.await;
+		self.observe(info, result)
+	}
+
+	async fn whois_leader(&self, request: Request<WhoisLeaderRequest>) -> Result<Response<WhoisLeaderResponse>, Status> {
+		let info = RpcInfo::of("WhoisLeader", &request, &request.get_ref().group_id);
+		let result = async { self.route(&info.group_id)?.whois_leader(request).await }.await;
+		self.observe(info, result)
+	}
+
+	async fn follow_events(&self, request: Request<FollowEventsRequest>) -> Result<Response<ElectionEventStream>, Status> {
+		let info = RpcInfo::of("FollowEvents", &request, &request.get_ref().group_id);
+		let result = async { self.route(&info.group_id)?.follow_events(request).await }.await;
+		self.observe(info, result)
+	}
+}
@@ -0,0 +1,46 @@
+use tonic::metadata::MetadataValue;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+use super::Node;
+
+/// Metadata key carrying the current leader's address on requests rejected by [`leader_guard`].
+pub const LEADER_ADDR_METADATA: &str = "x-leader-addr";
+
+/// Builds an interceptor that only lets requests through on the leader.
+///
+/// `current_leader` is called for every request and returns the leader this node currently
+/// follows; the request passes when that leader's id is `self_id`. Otherwise the caller gets
+/// `Status::failed_precondition("not leader")`, with the known leader's address in the
+/// [`LEADER_ADDR_METADATA`] entry so it can redirect. Keep `current_leader` cheap and
+/// non-blocking: it runs on the request path.
+///
+/// ```
+/// use services::election_service::{leader_guard, Node, LEADER_ADDR_METADATA};
+/// use tonic::service::Interceptor;
+///
+/// let leader = Node { id: 5, addr: "10.0.0.5:50051".to_string(), ..Default::default() };
+/// let mut follower = leader_guard(3, move || Some(leader.clone()));
+///
+/// let rejected = follower.call(tonic::Request::new(())).unwrap_err();
+/// assert_eq!(rejected.code(), tonic::Code::FailedPrecondition);
+/// assert_eq!(rejected.metadata().get(LEADER_ADDR_METADATA).unwrap(), "10.0.0.5:50051");
+/// ```
+pub fn leader_guard<F>(self_id: i64, current_leader: F) -> impl Interceptor + Clone
+where
+	F: Fn() -> Option<Node> + Clone + Send + Sync + 'static,
+{
+	move |request: Request<()>| {
+		let leader = current_leader();
+		if leader.as_ref().is_some_and(|leader| leader.id == self_id) {
+			return Ok(request);
+		}
+
+		let mut status = Status::failed_precondition("not leader");
+		let addr = leader.and_then(|leader| MetadataValue::try_from(leader.addr).ok());
+		if let Some(addr) = addr {
+			status.metadata_mut().insert(LEADER_ADDR_METADATA, addr);
+		}
+		Err(status)
+	}
+}
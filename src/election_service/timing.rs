@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+/// Election timeout for a node with `higher_peers` peers above it: `base + per_peer * higher_peers`, capped at `max`.
+///
+/// Larger clusters need longer to converge, so a fixed timeout is either too slow for three
+/// nodes or too eager for fifty. Saturates instead of overflowing.
+///
+/// ```
+/// use std::time::Duration;
+/// use services::election_service::scaled_election_timeout;
+///
+/// let (base, per_peer, max) = (Duration::from_millis(500), Duration::from_millis(50), Duration::from_secs(2));
+/// let timeout = |peers| scaled_election_timeout(base, per_peer, peers, max);
+///
+/// assert_eq!(timeout(0), Duration::from_millis(500));
+/// assert_eq!(timeout(2), Duration::from_millis(600));
+/// assert_eq!(timeout(10), Duration::from_millis(1000));
+/// assert_eq!(timeout(50), max);
+/// assert_eq!(timeout(usize::MAX), max);
+/// ```
+pub fn scaled_election_timeout(base: Duration, per_peer: Duration, higher_peers: usize, max: Duration) -> Duration {
+	let peers = u32::try_from(higher_peers).unwrap_or(u32::MAX);
+	base.saturating_add(per_peer.saturating_mul(peers)).min(max)
+}
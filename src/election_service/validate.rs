@@ -0,0 +1,121 @@
+use tonic::Status;
+use tonic::codegen::http::uri::Authority;
+
+use super::Node;
+
+/// Longest node address accepted: a maximal DNS name plus `:65535`.
+pub const MAX_ADDR_LEN: usize = 253 + 6;
+
+/// Checks a node address received over the wire before it is stored or dialled.
+///
+/// Accepts a plain `host:port` authority of at most [`MAX_ADDR_LEN`] bytes with a non-empty host
+/// and a port in `1..=65535`; anything else, including user info and paths, is rejected with
+/// `Status::invalid_argument`.
+///
+/// ```
+/// use services::election_service::validate_addr;
+///
+/// assert!(validate_addr("10.0.0.5:50051").is_ok());
+/// assert!(validate_addr("node-1.cluster.local:50051").is_ok());
+/// assert!(validate_addr("[::1]:50051").is_ok());
+///
+/// for malformed in ["10.0.0.5", "10.0.0.5:50051/admin", "user@10.0.0.5:50051", "10.0.0.5:port"] {
+///     assert_eq!(validate_addr(malformed).unwrap_err().code(), tonic::Code::InvalidArgument);
+/// }
+/// let oversized = format!("{}.example:50051", "a".repeat(300));
+/// assert_eq!(validate_addr(&oversized).unwrap_err().code(), tonic::Code::InvalidArgument);
+///
+/// let reason = |addr| validate_addr(addr).unwrap_err().message().to_string();
+/// assert_eq!(reason(":50051"), "invalid node address: missing host");
+/// assert_eq!(reason("10.0.0.5:0"), "invalid node address: port must not be 0");
+/// assert_eq!(reason("10.0.0.5:65536"), "invalid node address: port out of range");
+/// assert_eq!(reason("10.0.0.5:"), "invalid node address: missing port");
+/// ```
+pub fn validate_addr(addr: &str) -> Result<(), Status> {
+	check_addr(addr).map_err(|reason| Status::invalid_argument(format!("invalid node address: {reason}")))
+}
+
+/// Checks the `from` node of an incoming request, telling a missing sender apart from a malformed one.
+///
+/// Every rejection is `Status::invalid_argument`: "missing from" when the field is unset,
+/// otherwise an "invalid from" message naming the problem (an id that is not positive, an
+/// empty address, or an address [`validate_addr`] rejects).
+///
+/// ```
+/// use services::election_service::{validate_from, ElectionRequest, Node};
+///
+/// let valid = Node { id: 7, addr: "10.0.0.7:50051".to_string(), ..Default::default() };
+/// assert_eq!(validate_from(Some(&valid)).unwrap(), &valid);
+///
+/// let missing = ElectionRequest::default();
+/// let status = validate_from(missing.from.as_ref()).unwrap_err();
+/// assert_eq!((status.code(), status.message()), (tonic::Code::InvalidArgument, "missing from"));
+///
+/// let empty_addr = Node { addr: String::new(), ..valid.clone() };
+/// let status = validate_from(Some(&empty_addr)).unwrap_err();
+/// assert_eq!((status.code(), status.message()), (tonic::Code::InvalidArgument, "invalid from: empty addr"));
+///
+/// let negative_id = Node { id: -3, ..valid.clone() };
+/// let status = validate_from(Some(&negative_id)).unwrap_err();
+/// assert_eq!(status.code(), tonic::Code::InvalidArgument);
+/// assert_eq!(status.message(), "invalid from: node id must be positive, got -3");
+/// ```
+pub fn validate_from(from: Option<&Node>) -> Result<&Node, Status> {
+	let from = from.ok_or_else(|| Status::invalid_argument("missing from"))?;
+	if from.id <= 0 {
+		return Err(Status::invalid_argument(format!("invalid from: node id must be positive, got {}", from.id)));
+	}
+	if from.addr.is_empty() {
+		return Err(Status::invalid_argument("invalid from: empty addr"));
+	}
+	check_addr(&from.addr).map_err(|reason| Status::invalid_argument(format!("invalid from: {reason}")))?;
+	Ok(from)
+}
+
+/// [`validate_from`] for a request received by node `self_id`, also rejecting a sender that
+/// claims to be the receiver itself.
+///
+/// A node never messages itself, so such a request is misrouted or forged; it is rejected with
+/// `Status::invalid_argument` before it can, say, win an election against its own id.
+///
+/// ```
+/// use services::election_service::{validate_from_peer, Node};
+///
+/// let peer = Node { id: 7, addr: "10.0.0.7:50051".to_string(), ..Default::default() };
+/// assert_eq!(validate_from_peer(Some(&peer), 3).unwrap(), &peer);
+///
+/// let status = validate_from_peer(Some(&peer), 7).unwrap_err();
+/// assert_eq!(status.code(), tonic::Code::InvalidArgument);
+/// assert_eq!(status.message(), "invalid from: node 7 is this node");
+///
+/// // Everything validate_from rejects is still rejected.
+/// assert_eq!(validate_from_peer(None, 3).unwrap_err().message(), "missing from");
+/// ```
+pub fn validate_from_peer(from: Option<&Node>, self_id: i64) -> Result<&Node, Status> {
+	let from = validate_from(from)?;
+	if from.id == self_id {
+		return Err(Status::invalid_argument(format!("invalid from: node {} is this node", from.id)));
+	}
+	Ok(from)
+}
+
+/// Shared with `endpoint`, which reports the same problems as a configuration error.
+pub(super) fn check_addr(addr: &str) -> Result<(), String> {
+	if addr.len() > MAX_ADDR_LEN {
+		return Err(format!("longer than {MAX_ADDR_LEN} bytes"));
+	}
+	let authority: Authority = addr.parse().map_err(|_| "not a host:port authority".to_string())?;
+	if authority.as_str().contains('@') {
+		return Err("must not carry user info".to_string());
+	}
+	if authority.host().is_empty() {
+		return Err("missing host".to_string());
+	}
+	let port = authority.as_str()[authority.host().len()..].trim_start_matches(':');
+	match port.parse::<u16>() {
+		_ if port.is_empty() => Err("missing port".to_string()),
+		Ok(0) => Err("port must not be 0".to_string()),
+		Ok(_) => Ok(()),
+		Err(_) => Err("port out of range".to_string()),
+	}
+}
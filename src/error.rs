@@ -0,0 +1,121 @@
+//! Error type shared by the helpers built on top of the generated code.
+
+use std::error::Error;
+use std::{fmt, io};
+
+use tonic::{Code, Status};
+
+/// Everything the crate's own APIs can fail with.
+///
+/// Converts from `tonic::transport::Error` and `tonic::Status`, so `?` works on generated client calls.
+/// Either one becomes [`ServicesError::Timeout`] when a timeout caused it: a `DeadlineExceeded`
+/// status, tonic's own request timeout, or a connect timeout.
+///
+/// ```
+/// use std::time::Duration;
+/// use services::election_service::{connect_node, Node, PingRequest};
+/// use services::ServicesError;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let timeout = Some(Duration::from_millis(100));
+///
+/// // Accepts TCP connections but never answers a request.
+/// let stalled = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+/// let node = Node { addr: stalled.local_addr()?.to_string(), ..Default::default() };
+/// let (mut client, _) = connect_node(&node, timeout).await?;
+/// let status = client.ping(PingRequest::default()).await.unwrap_err();
+/// assert!(matches!(ServicesError::from(status), ServicesError::Timeout));
+///
+/// // On Linux, a listener whose accept backlog is full silently drops new connection
+/// // attempts; other platforms may refuse them instead, so only check there.
+/// if cfg!(target_os = "linux") {
+///     let socket = tokio::net::TcpSocket::new_v4()?;
+///     socket.bind("127.0.0.1:0".parse()?)?;
+///     let full = socket.listen(0)?;
+///     let _queued = tokio::net::TcpStream::connect(full.local_addr()?).await?;
+///     let node = Node { addr: full.local_addr()?.to_string(), ..Default::default() };
+///     assert!(matches!(connect_node(&node, timeout).await, Err(ServicesError::Timeout)));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub enum ServicesError {
+	/// Invalid configuration or input, e.g. a node address that is not a valid URI.
+	Config(String),
+	/// Failed to establish or keep a connection to a peer.
+	Transport(tonic::transport::Error),
+	/// A peer did not answer in time.
+	Timeout,
+	/// Too few nodes agreed: `votes` out of the `required` (weighted) majority.
+	NoQuorum { votes: u64, required: u64 },
+	/// The peer rejected the caller's credentials or identity.
+	Auth(Status),
+	/// Encrypting or decrypting a payload failed.
+	Crypto(String),
+	/// The directory service rejected or could not serve a request.
+	Directory(String),
+	/// Any other error status returned by a peer.
+	Rpc(Status),
+}
+
+impl fmt::Display for ServicesError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Config(message) => write!(f, "invalid configuration: {message}"),
+			Self::Transport(err) => write!(f, "transport error: {err}"),
+			Self::Timeout => write!(f, "timed out waiting for peer"),
+			Self::NoQuorum { votes, required } => write!(f, "no quorum: {votes} of {required} required votes"),
+			Self::Auth(status) => write!(f, "authentication failed: {}", status.message()),
+			Self::Crypto(message) => write!(f, "crypto error: {message}"),
+			Self::Directory(message) => write!(f, "directory error: {message}"),
+			Self::Rpc(status) => write!(f, "rpc failed ({:?}): {}", status.code(), status.message()),
+		}
+	}
+}
+
+impl Error for ServicesError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			Self::Transport(err) => Some(err),
+			Self::Auth(status) | Self::Rpc(status) => Some(status),
+			_ => None,
+		}
+	}
+}
+
+impl From<tonic::transport::Error> for ServicesError {
+	fn from(err: tonic::transport::Error) -> Self {
+		if caused_by_timeout(&err) {
+			return Self::Timeout;
+		}
+		Self::Transport(err)
+	}
+}
+
+impl From<Status> for ServicesError {
+	fn from(status: Status) -> Self {
+		if caused_by_timeout(&status) {
+			return Self::Timeout;
+		}
+		match status.code() {
+			Code::DeadlineExceeded => Self::Timeout,
+			Code::Unauthenticated | Code::PermissionDenied => Self::Auth(status),
+			_ => Self::Rpc(status),
+		}
+	}
+}
+
+/// Walks `err`'s source chain for tonic's request timeout or a timed-out connect.
+fn caused_by_timeout(err: &(dyn Error + 'static)) -> bool {
+	let mut source = Some(err);
+	while let Some(err) = source {
+		let timed_out_io = err.downcast_ref::<io::Error>().is_some_and(|err| err.kind() == io::ErrorKind::TimedOut);
+		if timed_out_io || err.is::<tonic::TimeoutExpired>() {
+			return true;
+		}
+		source = err.source();
+	}
+	false
+}
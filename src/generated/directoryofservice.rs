@@ -1,6 +1,6 @@
 // This file is @generated by prost-build.
 /// Peer represents an online peer in the directory.
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Peer {
     /// IP address of the peer
     #[prost(string, tag = "1")]
@@ -8,6 +8,12 @@ pub struct Peer {
     /// Username of the peer
     #[prost(string, tag = "2")]
     pub username: ::prost::alloc::string::String,
+    /// Optional deployment metadata (zone, role, ...)
+    #[prost(map = "string, string", tag = "3")]
+    pub attributes: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
 }
 /// Request represents a pending request in the directory.
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
@@ -36,7 +42,7 @@ pub struct Request {
     pub timestamp: i64,
 }
 /// Request message for registering a peer.
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RegisterPeerRequest {
     /// IP address of the peer
     #[prost(string, tag = "1")]
@@ -44,6 +50,12 @@ pub struct RegisterPeerRequest {
     /// Username of the peer
     #[prost(string, tag = "2")]
     pub username: ::prost::alloc::string::String,
+    /// Optional deployment metadata (zone, role, ...)
+    #[prost(map = "string, string", tag = "3")]
+    pub attributes: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
 }
 /// Response for peer registration.
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
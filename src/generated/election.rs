@@ -1,6 +1,6 @@
 // This file is @generated by prost-build.
 /// Messages used for the Bully election algorithm
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Node {
     /// Unix epoch time in milliseconds used as the node's numeric id.
     /// ms to indicate the time a node was elected as a leader.
@@ -11,14 +11,35 @@ pub struct Node {
     /// transport address (host:port) to contact this node
     #[prost(string, tag = "2")]
     pub addr: ::prost::alloc::string::String,
+    /// Opaque deployment metadata (zone, role, capacity, ...). Empty when the
+    /// sender has none, which is also what older peers decode it as.
+    #[prost(map = "string, string", tag = "3")]
+    pub attributes: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Weight of this node's vote in quorum checks (e.g. a primary DC counts
+    /// more). 0 (unset) counts as 1, so unweighted clusters keep plain majority.
+    #[prost(uint32, tag = "4")]
+    pub vote_weight: u32,
+    /// Further addresses this node is reachable at (e.g. internal and external
+    /// interfaces), tried in order after addr.
+    #[prost(string, repeated, tag = "5")]
+    pub additional_addrs: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ElectionRequest {
     /// who is starting the election
     #[prost(message, optional, tag = "1")]
     pub from: ::core::option::Option<Node>,
+    /// sender's highest supported protocol version (0 = v1)
+    #[prost(uint32, tag = "2")]
+    pub protocol_version: u32,
+    /// election group this message belongs to ("" = default group)
+    #[prost(string, tag = "3")]
+    pub group_id: ::prost::alloc::string::String,
 }
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ElectionResponse {
     /// indicates higher-id node is alive and will take over election
     #[prost(bool, tag = "1")]
@@ -26,22 +47,209 @@ pub struct ElectionResponse {
     /// the responding node with current ID (Modified Bully Step 2(ii))
     #[prost(message, optional, tag = "2")]
     pub responder: ::core::option::Option<Node>,
+    /// responder's highest supported protocol version (0 = v1)
+    #[prost(uint32, tag = "3")]
+    pub protocol_version: u32,
 }
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Coordinator {
     /// announced leader node
     #[prost(message, optional, tag = "1")]
     pub leader: ::core::option::Option<Node>,
+    /// monotonically increasing leadership term of this announcement
+    #[prost(uint64, tag = "2")]
+    pub epoch: u64,
+    /// UUID identifying this announcement. Retries and re-broadcasts reuse the
+    /// same id so receivers can drop exact duplicates before doing any work.
+    #[prost(string, tag = "3")]
+    pub announcement_id: ::prost::alloc::string::String,
+    /// announcer's highest supported protocol version (0 = v1)
+    #[prost(uint32, tag = "4")]
+    pub protocol_version: u32,
+    /// election group this announcement belongs to ("" = default group)
+    #[prost(string, tag = "5")]
+    pub group_id: ::prost::alloc::string::String,
+    /// Remaining re-broadcast hops for gossip-style propagation. A node forwarding
+    /// the announcement sends max_hops - 1 and stops forwarding once it receives
+    /// 0. Unset means the announcement carries no hop limit.
+    #[prost(uint32, optional, tag = "6")]
+    pub max_hops: ::core::option::Option<u32>,
 }
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PingRequest {
     #[prost(message, optional, tag = "1")]
     pub from: ::core::option::Option<Node>,
+    /// sender's highest supported protocol version (0 = v1)
+    #[prost(uint32, tag = "2")]
+    pub protocol_version: u32,
+    /// election group being probed ("" = default group)
+    #[prost(string, tag = "3")]
+    pub group_id: ::prost::alloc::string::String,
+    /// optional features the sender supports
+    #[prost(string, repeated, tag = "4")]
+    pub capabilities: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
-#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PingResponse {
     #[prost(bool, tag = "1")]
     pub alive: bool,
+    /// Responder's current leader view, so a stale leader can demote as soon as
+    /// a ping reveals a newer one. Zero when the responder knows no leader.
+    ///
+    /// id of the leader the responder follows
+    #[prost(int64, tag = "2")]
+    pub leader_id: i64,
+    /// epoch at which that leader was announced
+    #[prost(uint64, tag = "3")]
+    pub epoch: u64,
+    /// responder's highest supported protocol version (0 = v1)
+    #[prost(uint32, tag = "4")]
+    pub protocol_version: u32,
+    /// Responder's wall clock (unix ms) when it answered, so the caller can
+    /// estimate one-way delay next to its measured round trip. 0 if not reported.
+    #[prost(int64, tag = "5")]
+    pub server_time_ms: i64,
+    /// Whether the responder could open a connection back to PingRequest.from.
+    /// Unset when the responder did not attempt a reverse probe; false flags an
+    /// asymmetric link.
+    #[prost(bool, optional, tag = "6")]
+    pub can_reach_back: ::core::option::Option<bool>,
+    /// Responder's self-report, so a single ping doubles as a state-sync probe
+    /// for membership reconciliation. Unset / ROLE_UNSPECIFIED from older peers.
+    #[prost(message, optional, tag = "7")]
+    pub responder: ::core::option::Option<Node>,
+    #[prost(enumeration = "Role", tag = "8")]
+    pub role: i32,
+    /// Optional features the responder supports (see election_service::capabilities),
+    /// so callers can e.g. subscribe instead of polling only where supported.
+    #[prost(string, repeated, tag = "9")]
+    pub capabilities: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribeCoordinatorRequest {
+    /// subscribing follower
+    #[prost(message, optional, tag = "1")]
+    pub from: ::core::option::Option<Node>,
+    /// election group to follow ("" = default group)
+    #[prost(string, tag = "2")]
+    pub group_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct WhoisLeaderRequest {
+    /// election group to ask about ("" = default group)
+    #[prost(string, tag = "1")]
+    pub group_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WhoisLeaderResponse {
+    /// responder's current leader, unset if it knows none
+    #[prost(message, optional, tag = "1")]
+    pub leader: ::core::option::Option<Node>,
+    /// epoch at which that leader was announced
+    #[prost(uint64, tag = "2")]
+    pub epoch: u64,
+}
+/// Election event log entry, as streamed to remote observers by FollowEvents.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ElectionEvent {
+    #[prost(enumeration = "ElectionEventKind", tag = "1")]
+    pub kind: i32,
+    /// when the event happened (unix ms)
+    #[prost(int64, tag = "2")]
+    pub timestamp_ms: i64,
+    /// subject of the event: winner, new leader, or the peer that went down
+    #[prost(message, optional, tag = "3")]
+    pub node: ::core::option::Option<Node>,
+    /// leadership epoch the event belongs to
+    #[prost(uint64, tag = "4")]
+    pub epoch: u64,
+    /// election group the event belongs to ("" = default group)
+    #[prost(string, tag = "5")]
+    pub group_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct FollowEventsRequest {
+    /// number of most recent events to replay before live ones
+    #[prost(uint32, tag = "1")]
+    pub replay: u32,
+    /// election group to follow ("" = default group)
+    #[prost(string, tag = "2")]
+    pub group_id: ::prost::alloc::string::String,
+}
+/// Role a node reports for itself in PingResponse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Role {
+    /// responder did not report a role
+    Unspecified = 0,
+    Follower = 1,
+    /// election in progress
+    Candidate = 2,
+    Leader = 3,
+}
+impl Role {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "ROLE_UNSPECIFIED",
+            Self::Follower => "ROLE_FOLLOWER",
+            Self::Candidate => "ROLE_CANDIDATE",
+            Self::Leader => "ROLE_LEADER",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ROLE_UNSPECIFIED" => Some(Self::Unspecified),
+            "ROLE_FOLLOWER" => Some(Self::Follower),
+            "ROLE_CANDIDATE" => Some(Self::Candidate),
+            "ROLE_LEADER" => Some(Self::Leader),
+            _ => None,
+        }
+    }
+}
+/// What an ElectionEvent reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ElectionEventKind {
+    EventUnspecified = 0,
+    /// node started an election
+    ElectionStarted = 1,
+    /// node won an election and is announcing itself
+    ElectionWon = 2,
+    /// node adopted a new leader
+    LeaderChanged = 3,
+    /// node considers a peer unreachable
+    PeerDown = 4,
+}
+impl ElectionEventKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::EventUnspecified => "EVENT_UNSPECIFIED",
+            Self::ElectionStarted => "ELECTION_STARTED",
+            Self::ElectionWon => "ELECTION_WON",
+            Self::LeaderChanged => "LEADER_CHANGED",
+            Self::PeerDown => "PEER_DOWN",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "EVENT_UNSPECIFIED" => Some(Self::EventUnspecified),
+            "ELECTION_STARTED" => Some(Self::ElectionStarted),
+            "ELECTION_WON" => Some(Self::ElectionWon),
+            "LEADER_CHANGED" => Some(Self::LeaderChanged),
+            "PEER_DOWN" => Some(Self::PeerDown),
+            _ => None,
+        }
+    }
 }
 /// Generated client implementations.
 pub mod bully_client {
@@ -221,6 +429,86 @@ pub mod bully_client {
             req.extensions_mut().insert(GrpcMethod::new("election.Bully", "Ping"));
             self.inner.unary(req, path, codec).await
         }
+        /// Long-lived stream of leader changes for passive followers, so the leader
+        /// does not have to push every announcement itself. Each leader change the
+        /// serving node observes is sent as a Coordinator. Followers reconnect and
+        /// resubscribe when the stream breaks.
+        pub async fn subscribe_coordinator(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SubscribeCoordinatorRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::Coordinator>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/election.Bully/SubscribeCoordinator",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("election.Bully", "SubscribeCoordinator"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// Side-effect free query of the responder's current (leader, epoch) view.
+        /// Meant for thin clients outside the cluster; never triggers an election.
+        pub async fn whois_leader(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WhoisLeaderRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::WhoisLeaderResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/election.Bully/WhoisLeader",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("election.Bully", "WhoisLeader"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Tail the node's election event log remotely: replays up to
+        /// FollowEventsRequest.replay recent events, then streams new ones live.
+        pub async fn follow_events(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FollowEventsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::ElectionEvent>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/election.Bully/FollowEvents",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("election.Bully", "FollowEvents"));
+            self.inner.server_streaming(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -243,23 +531,66 @@ pub mod bully_server {
         ) -> std::result::Result<
             tonic::Response<super::ElectionResponse>,
             tonic::Status,
-        >;
+        > {
+            Err(tonic::Status::unimplemented("Not yet implemented"))
+        }
         /// Announces the final leader to all nodes. Leader sends to all peers announcing they're winning.
         async fn announce_coordinator(
             &self,
             request: tonic::Request<super::Coordinator>,
-        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status>;
+        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status> {
+            Err(tonic::Status::unimplemented("Not yet implemented"))
+        }
         /// Modified Bully Step 2(iv): Notify a node that it should become coordinator
         /// and validate itself before announcing.
         async fn notify_coordinator(
             &self,
             request: tonic::Request<super::Coordinator>,
-        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status>;
+        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status> {
+            Err(tonic::Status::unimplemented("Not yet implemented"))
+        }
         /// Simple ping used to check aliveness.
         async fn ping(
             &self,
             request: tonic::Request<super::PingRequest>,
-        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status>;
+        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status> {
+            Err(tonic::Status::unimplemented("Not yet implemented"))
+        }
+        /// Long-lived stream of leader changes for passive followers, so the leader
+        /// does not have to push every announcement itself. Each leader change the
+        /// serving node observes is sent as a Coordinator. Followers reconnect and
+        /// resubscribe when the stream breaks.
+        async fn subscribe_coordinator(
+            &self,
+            request: tonic::Request<super::SubscribeCoordinatorRequest>,
+        ) -> std::result::Result<
+            tonic::Response<BoxStream<super::Coordinator>>,
+            tonic::Status,
+        > {
+            Err(tonic::Status::unimplemented("Not yet implemented"))
+        }
+        /// Side-effect free query of the responder's current (leader, epoch) view.
+        /// Meant for thin clients outside the cluster; never triggers an election.
+        async fn whois_leader(
+            &self,
+            request: tonic::Request<super::WhoisLeaderRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::WhoisLeaderResponse>,
+            tonic::Status,
+        > {
+            Err(tonic::Status::unimplemented("Not yet implemented"))
+        }
+        /// Tail the node's election event log remotely: replays up to
+        /// FollowEventsRequest.replay recent events, then streams new ones live.
+        async fn follow_events(
+            &self,
+            request: tonic::Request<super::FollowEventsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<BoxStream<super::ElectionEvent>>,
+            tonic::Status,
+        > {
+            Err(tonic::Status::unimplemented("Not yet implemented"))
+        }
     }
     /// Service exposing the basic RPCs used by the Bully algorithm.
     #[derive(Debug)]
@@ -510,6 +841,142 @@ pub mod bully_server {
                     };
                     Box::pin(fut)
                 }
+                "/election.Bully/SubscribeCoordinator" => {
+                    #[allow(non_camel_case_types)]
+                    struct SubscribeCoordinatorSvc<T: Bully>(pub Arc<T>);
+                    impl<
+                        T: Bully,
+                    > tonic::server::ServerStreamingService<
+                        super::SubscribeCoordinatorRequest,
+                    > for SubscribeCoordinatorSvc<T> {
+                        type Response = super::Coordinator;
+                        type ResponseStream = BoxStream<super::Coordinator>;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SubscribeCoordinatorRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Bully>::subscribe_coordinator(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = SubscribeCoordinatorSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/election.Bully/WhoisLeader" => {
+                    #[allow(non_camel_case_types)]
+                    struct WhoisLeaderSvc<T: Bully>(pub Arc<T>);
+                    impl<T: Bully> tonic::server::UnaryService<super::WhoisLeaderRequest>
+                    for WhoisLeaderSvc<T> {
+                        type Response = super::WhoisLeaderResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WhoisLeaderRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Bully>::whois_leader(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = WhoisLeaderSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/election.Bully/FollowEvents" => {
+                    #[allow(non_camel_case_types)]
+                    struct FollowEventsSvc<T: Bully>(pub Arc<T>);
+                    impl<
+                        T: Bully,
+                    > tonic::server::ServerStreamingService<super::FollowEventsRequest>
+                    for FollowEventsSvc<T> {
+                        type Response = super::ElectionEvent;
+                        type ResponseStream = BoxStream<super::ElectionEvent>;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FollowEventsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Bully>::follow_events(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = FollowEventsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         let mut response = http::Response::new(
@@ -1,14 +1,135 @@
-/// This module contains the auto-generated code by the build script.
-/// to be used in the client and server implementations.
+//! This module contains the auto-generated code by the build script.
+//! to be used in the client and server implementations.
 
 pub mod encryption_service {
 	include!("generated/encryption.rs");
 }
 
+/// Bully election protocol messages, client and server.
+///
+/// The generated `Bully` trait has a default body for every RPC that answers
+/// `Status::unimplemented`, so implementations written against an older `election.proto`
+/// keep compiling when RPCs are added and peers see `Unimplemented` for the ones they lack.
+///
+/// ```
+/// use services::election_service::bully_server::Bully;
+/// use services::election_service::{Coordinator, ElectionRequest, ElectionResponse, PingRequest, PingResponse};
+/// use services::election_service::{FollowEventsRequest, SubscribeCoordinatorRequest, WhoisLeaderRequest};
+/// use tonic::{Request, Response, Status};
+///
+/// // Implements only the original Bully RPCs.
+/// struct LegacyBully;
+///
+/// #[tonic::async_trait]
+/// impl Bully for LegacyBully {
+///     async fn election(&self, _: Request<ElectionRequest>) -> Result<Response<ElectionResponse>, Status> {
+///         Ok(Response::new(ElectionResponse::default()))
+///     }
+///     async fn announce_coordinator(&self, _: Request<Coordinator>) -> Result<Response<PingResponse>, Status> {
+///         Ok(Response::new(PingResponse::default()))
+///     }
+///     async fn notify_coordinator(&self, _: Request<Coordinator>) -> Result<Response<PingResponse>, Status> {
+///         Ok(Response::new(PingResponse::default()))
+///     }
+///     async fn ping(&self, _: Request<PingRequest>) -> Result<Response<PingResponse>, Status> {
+///         Ok(Response::new(PingResponse { alive: true, ..Default::default() }))
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let legacy = LegacyBully;
+/// let subscribe = legacy.subscribe_coordinator(Request::new(SubscribeCoordinatorRequest::default())).await;
+/// assert_eq!(subscribe.err().map(|status| status.code()), Some(tonic::Code::Unimplemented));
+/// let whois = legacy.whois_leader(Request::new(WhoisLeaderRequest::default())).await;
+/// assert_eq!(whois.unwrap_err().code(), tonic::Code::Unimplemented);
+/// let events = legacy.follow_events(Request::new(FollowEventsRequest::default())).await;
+/// assert_eq!(events.err().map(|status| status.code()), Some(tonic::Code::Unimplemented));
+/// # }
+/// ```
 pub mod election_service {
     include!("generated/election.rs");
+
+    mod announce;
+    pub mod capabilities;
+    mod client;
+    mod detector;
+    mod groups;
+    mod guard;
+    mod timing;
+    mod validate;
+    pub use announce::{check_epoch_jump, RecentAnnouncements, DEFAULT_RECENT_ANNOUNCEMENTS};
+    pub use client::{
+        cluster_consistency, connect_node, endpoint_for, follow_coordinator, ping_peer, whois_leader_hedged,
+        whois_majority_leader, ConsistencyReport, Hedge, PingSample, ReconnectBackoff, ViewGroup, DEFAULT_HEDGE_DELAY,
+        DEFAULT_HEDGE_FANOUT, DEFAULT_KEEPALIVE_INTERVAL, DEFAULT_RECONNECT_INITIAL, DEFAULT_RECONNECT_MAX,
+        DEFAULT_RECONNECT_TIMEOUT, MIN_RECONNECT_DELAY,
+    };
+    pub use detector::{PhiAccrual, DEFAULT_PHI_MIN_STD_DEV, DEFAULT_PHI_THRESHOLD, DEFAULT_PHI_WINDOW};
+    pub use groups::{CoordinatorStream, ElectionEventStream, MultiGroupElectionService, RpcInfo};
+    pub use guard::{leader_guard, LEADER_ADDR_METADATA};
+    pub use timing::scaled_election_timeout;
+    pub use validate::{validate_addr, validate_from, validate_from_peer, MAX_ADDR_LEN};
+
+    /// Highest election protocol version described by `election.proto`.
+    /// Peers negotiate down to the lowest version both sides advertise.
+    pub const PROTOCOL_VERSION: u32 = 3;
+
+    /// Version a peer effectively speaks: an unset (`0`) field means version 1.
+    ///
+    /// ```
+    /// use services::election_service::negotiated_version;
+    ///
+    /// assert_eq!(negotiated_version(0), 1);
+    /// assert_eq!(negotiated_version(2), 2);
+    /// assert_eq!(negotiated_version(u32::MAX), services::election_service::PROTOCOL_VERSION);
+    /// ```
+    pub fn negotiated_version(peer_version: u32) -> u32 {
+        peer_version.clamp(1, PROTOCOL_VERSION)
+    }
+
+    /// `attributes` round-trip through the wire format, and encodings from peers that predate
+    /// the field decode to an empty map.
+    ///
+    /// ```
+    /// use prost::Message;
+    /// use services::election_service::Node;
+    ///
+    /// let attributes = [("zone", "eu-west-1a"), ("role", "storage")];
+    /// let node = Node {
+    ///     id: 3,
+    ///     addr: "10.0.0.3:50051".to_string(),
+    ///     attributes: attributes.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(Node::decode(node.encode_to_vec().as_slice())?, node);
+    ///
+    /// // `Node { id: 3, addr: "a:1" }` as encoded before `attributes` existed.
+    /// let legacy = Node::decode(&[0x08, 0x03, 0x12, 0x03, b'a', b':', b'1'][..])?;
+    /// assert_eq!((legacy.id, legacy.addr.as_str()), (3, "a:1"));
+    /// assert!(legacy.attributes.is_empty());
+    /// # Ok::<(), prost::DecodeError>(())
+    /// ```
+    impl Node {
+        /// Weight of this node in quorum tallies; an unset (`0`) weight counts as 1.
+        pub fn effective_vote_weight(&self) -> u64 {
+            u64::from(self.vote_weight.max(1))
+        }
+    }
 }
 
 pub mod directory_of_service {
     include!("generated/directoryofservice.rs");
-}
\ No newline at end of file
+}
+
+/// Encoded `FileDescriptorSet` covering every proto in `protos/`, for reflection
+/// and other dynamic message handling at runtime.
+pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!("generated/services_descriptor.bin");
+
+pub mod error;
+pub use error::ServicesError;
+
+pub use election_service::bully_server::BullyServer;
+
+#[cfg(feature = "testing")]
+pub mod testing;
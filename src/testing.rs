@@ -0,0 +1,293 @@
+//! Test doubles for the generated service traits.
+//! Bind them to a tonic server in your own tests to observe what your code sends.
+//!
+//! Only built with the `testing` feature; enable it from `[dev-dependencies]`.
+
+use std::collections::VecDeque;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tokio::net::TcpSocket;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+use crate::election_service::bully_server::{Bully, BullyServer};
+use crate::election_service::{
+	capabilities, Coordinator, CoordinatorStream, ElectionEvent, ElectionEventStream, ElectionRequest, ElectionResponse,
+	FollowEventsRequest, PingRequest, PingResponse, SubscribeCoordinatorRequest, WhoisLeaderRequest, WhoisLeaderResponse,
+	PROTOCOL_VERSION,
+};
+
+/// Announcements and events buffered per subscriber before a slow one starts missing them.
+const SUBSCRIPTION_CAPACITY: usize = 16;
+/// Published events kept for `follow_events` replay.
+const EVENT_HISTORY: usize = 64;
+
+/// A single RPC received by a [`RecordingBully`], in arrival order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedCall {
+	Election(ElectionRequest),
+	AnnounceCoordinator(Coordinator),
+	NotifyCoordinator(Coordinator),
+	Ping(PingRequest),
+	SubscribeCoordinator(SubscribeCoordinatorRequest),
+	WhoisLeader(WhoisLeaderRequest),
+	FollowEvents(FollowEventsRequest),
+}
+
+/// `Bully` implementation that records every request it receives and answers with canned responses.
+///
+/// Clones share the same call log, so keep one clone and hand the other to `BullyServer`.
+///
+/// ```
+/// use services::election_service::bully_client::BullyClient;
+/// use services::election_service::{Node, PingRequest, PingResponse, Role};
+/// use services::testing::{RecordedCall, RecordingBully};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let me = Node { id: 2, ..Default::default() };
+/// let mock = RecordingBully::new().with_ping_response(PingResponse {
+///     alive: true,
+///     responder: Some(me.clone()),
+///     role: Role::Leader.into(),
+///     ..Default::default()
+/// });
+/// let addr = mock.clone().serve()?;
+///
+/// let mut client = BullyClient::connect(format!("http://{addr}")).await?;
+/// let from = Node { id: 1, addr: "127.0.0.1:50051".to_string(), ..Default::default() };
+/// let reply = client.ping(PingRequest { from: Some(from.clone()), ..Default::default() }).await?;
+///
+/// let reply = reply.into_inner();
+/// assert!(reply.alive);
+/// assert_eq!((reply.role(), reply.responder), (Role::Leader, Some(me)));
+/// assert_eq!(mock.calls(), vec![RecordedCall::Ping(PingRequest { from: Some(from), ..Default::default() })]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordingBully {
+	calls: Arc<Mutex<Vec<RecordedCall>>>,
+	election_response: ElectionResponse,
+	coordinator_response: PingResponse,
+	ping_response: PingResponse,
+	whois_response: WhoisLeaderResponse,
+	coordinators: broadcast::Sender<Coordinator>,
+	event_history: Arc<Mutex<VecDeque<ElectionEvent>>>,
+	events: broadcast::Sender<ElectionEvent>,
+}
+
+impl Default for RecordingBully {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl RecordingBully {
+	/// Creates a mock that rejects elections (`ok = false`) and acknowledges everything else,
+	/// advertising [`PROTOCOL_VERSION`] in every response and, in pings, the capabilities of the
+	/// RPCs it serves: `SubscribeCoordinator`, `WhoisLeader` and `FollowEvents`.
+	pub fn new() -> Self {
+		let ack = PingResponse { alive: true, protocol_version: PROTOCOL_VERSION, ..Default::default() };
+		let capabilities = [capabilities::SUBSCRIBE_COORDINATOR, capabilities::WHOIS_LEADER, capabilities::FOLLOW_EVENTS];
+		let ping_response = PingResponse { capabilities: capabilities.map(String::from).to_vec(), ..ack.clone() };
+		Self {
+			calls: Arc::new(Mutex::new(Vec::new())),
+			election_response: ElectionResponse { protocol_version: PROTOCOL_VERSION, ..Default::default() },
+			coordinator_response: ack,
+			ping_response,
+			whois_response: WhoisLeaderResponse::default(),
+			coordinators: broadcast::channel(SUBSCRIPTION_CAPACITY).0,
+			event_history: Arc::new(Mutex::new(VecDeque::with_capacity(EVENT_HISTORY))),
+			events: broadcast::channel(SUBSCRIPTION_CAPACITY).0,
+		}
+	}
+
+	/// Response returned by `election`.
+	#[must_use]
+	pub fn with_election_response(mut self, response: ElectionResponse) -> Self {
+		self.election_response = response;
+		self
+	}
+
+	/// Response returned by both `announce_coordinator` and `notify_coordinator`.
+	#[must_use]
+	pub fn with_coordinator_response(mut self, response: PingResponse) -> Self {
+		self.coordinator_response = response;
+		self
+	}
+
+	/// Response returned by `ping`.
+	#[must_use]
+	pub fn with_ping_response(mut self, response: PingResponse) -> Self {
+		self.ping_response = response;
+		self
+	}
+
+	/// Response returned by `whois_leader`; defaults to knowing no leader.
+	#[must_use]
+	pub fn with_whois_response(mut self, response: WhoisLeaderResponse) -> Self {
+		self.whois_response = response;
+		self
+	}
+
+	/// Serves this mock on a free loopback port from a background task and returns the bound address.
+	///
+	/// Must be called from within a tokio runtime. Clones share their call log, so serve a clone
+	/// to keep inspecting calls.
+	pub fn serve(self) -> io::Result<SocketAddr> {
+		self.serve_on(SocketAddr::from(([127, 0, 0, 1], 0)))
+	}
+
+	/// Like [`serve`](Self::serve), but binds `addr`, with `SO_REUSEADDR` so a test can bring a
+	/// node back up on the address it had before.
+	pub fn serve_on(self, addr: SocketAddr) -> io::Result<SocketAddr> {
+		let socket = if addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+		socket.set_reuseaddr(true)?;
+		socket.bind(addr)?;
+		let listener = socket.listen(1024)?;
+		let addr = listener.local_addr()?;
+		tokio::spawn(
+			Server::builder()
+				.add_service(BullyServer::new(self))
+				.serve_with_incoming(TcpIncoming::from(listener)),
+		);
+		Ok(addr)
+	}
+
+	/// Pushes `coordinator` to every open `subscribe_coordinator` stream.
+	///
+	/// ```
+	/// use services::election_service::bully_client::BullyClient;
+	/// use services::election_service::{Coordinator, Node, SubscribeCoordinatorRequest};
+	/// use services::testing::RecordingBully;
+	/// use tokio_stream::StreamExt;
+	///
+	/// # #[tokio::main]
+	/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+	/// let mock = RecordingBully::new();
+	/// let addr = mock.clone().serve()?;
+	///
+	/// let mut follower = BullyClient::connect(format!("http://{addr}")).await?;
+	/// let mut leaders = follower.subscribe_coordinator(SubscribeCoordinatorRequest::default()).await?.into_inner();
+	///
+	/// let leader = Node { id: 9, addr: "10.0.0.9:50051".to_string(), ..Default::default() };
+	/// let announcement = Coordinator { leader: Some(leader), epoch: 3, ..Default::default() };
+	/// mock.publish_coordinator(announcement.clone());
+	///
+	/// assert_eq!(leaders.next().await.transpose()?, Some(announcement));
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub fn publish_coordinator(&self, coordinator: Coordinator) {
+		// No subscribers is not an error for a mock.
+		let _ = self.coordinators.send(coordinator);
+	}
+
+	/// Appends `event` to the replayable event log and pushes it to every open `follow_events` stream.
+	///
+	/// ```
+	/// use services::election_service::bully_client::BullyClient;
+	/// use services::election_service::{ElectionEvent, ElectionEventKind, FollowEventsRequest};
+	/// use services::testing::RecordingBully;
+	/// use tokio_stream::StreamExt;
+	///
+	/// # #[tokio::main]
+	/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+	/// let mock = RecordingBully::new();
+	/// let addr = mock.clone().serve()?;
+	///
+	/// let started = ElectionEvent { kind: ElectionEventKind::ElectionStarted.into(), epoch: 1, ..Default::default() };
+	/// mock.publish_event(started.clone());
+	///
+	/// let mut observer = BullyClient::connect(format!("http://{addr}")).await?;
+	/// let mut events = observer.follow_events(FollowEventsRequest { replay: 1, ..Default::default() }).await?.into_inner();
+	/// let changed = ElectionEvent { kind: ElectionEventKind::LeaderChanged.into(), epoch: 1, ..Default::default() };
+	/// mock.publish_event(changed.clone());
+	///
+	/// assert_eq!(events.next().await.transpose()?, Some(started));
+	/// assert_eq!(events.next().await.transpose()?, Some(changed));
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub fn publish_event(&self, event: ElectionEvent) {
+		// Held across the send so a concurrent subscriber sees each event exactly once.
+		let mut history = self.event_history.lock().unwrap();
+		if history.len() == EVENT_HISTORY {
+			history.pop_front();
+		}
+		history.push_back(event.clone());
+		let _ = self.events.send(event);
+	}
+
+	/// Snapshot of every call received so far.
+	pub fn calls(&self) -> Vec<RecordedCall> {
+		self.calls.lock().unwrap().clone()
+	}
+
+	/// Forgets all recorded calls.
+	pub fn clear(&self) {
+		self.calls.lock().unwrap().clear();
+	}
+
+	fn record(&self, call: RecordedCall) {
+		self.calls.lock().unwrap().push(call);
+	}
+}
+
+#[tonic::async_trait]
+impl Bully for RecordingBully {
+
+	async fn election(&self, request: Request<ElectionRequest>) -> Result<Response<ElectionResponse>, Status> {
+		self.record(RecordedCall::Election(request.into_inner()));
+		Ok(Response::new(self.election_response.clone()))
+	}
+
+	async fn announce_coordinator(&self, request: Request<Coordinator>) -> Result<Response<PingResponse>, Status> {
+		self.record(RecordedCall::AnnounceCoordinator(request.into_inner()));
+		Ok(Response::new(self.coordinator_response.clone()))
+	}
+
+	async fn notify_coordinator(&self, request: Request<Coordinator>) -> Result<Response<PingResponse>, Status> {
+		self.record(RecordedCall::NotifyCoordinator(request.into_inner()));
+		Ok(Response::new(self.coordinator_response.clone()))
+	}
+
+	async fn ping(&self, request: Request<PingRequest>) -> Result<Response<PingResponse>, Status> {
+		self.record(RecordedCall::Ping(request.into_inner()));
+		Ok(Response::new(self.ping_response.clone()))
+	}
+
+	async fn subscribe_coordinator(
+		&self,
+		request: Request<SubscribeCoordinatorRequest>,
+	) -> Result<Response<CoordinatorStream>, Status> {
+		self.record(RecordedCall::SubscribeCoordinator(request.into_inner()));
+		// Lagged subscribers just skip what they missed.
+		let stream = BroadcastStream::new(self.coordinators.subscribe()).filter_map(|item| item.ok().map(Ok));
+		Ok(Response::new(Box::pin(stream)))
+	}
+
+	async fn whois_leader(&self, request: Request<WhoisLeaderRequest>) -> Result<Response<WhoisLeaderResponse>, Status> {
+		self.record(RecordedCall::WhoisLeader(request.into_inner()));
+		Ok(Response::new(self.whois_response.clone()))
+	}
+
+	async fn follow_events(&self, request: Request<FollowEventsRequest>) -> Result<Response<ElectionEventStream>, Status> {
+		let request = request.into_inner();
+		let (replayed, live) = {
+			let history = self.event_history.lock().unwrap();
+			let skip = history.len().saturating_sub(request.replay as usize);
+			(history.iter().skip(skip).cloned().map(Ok).collect::<Vec<_>>(), self.events.subscribe())
+		};
+		self.record(RecordedCall::FollowEvents(request));
+		let live = BroadcastStream::new(live).filter_map(|item| item.ok().map(Ok));
+		Ok(Response::new(Box::pin(tokio_stream::iter(replayed).chain(live))))
+	}
+}